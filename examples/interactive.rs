@@ -11,26 +11,30 @@ struct State {
 }
 
 fn build_plot_widget() -> impl Widget<State> {
-    Plot::new(|_size, data: &State, root| {
+    Plot::with_ctx(|ctx, data: &State, root| {
         let μ = data.μ as f32;
 
         let res = 400;
-        let font = FontDesc::new(FontFamily::SansSerif, 16., FontStyle::Normal);
+        // Sizes are given in logical pixels and scaled to the device pixels of the drawing
+        // area.
+        let scale = ctx.scale();
+        let font = FontDesc::new(FontFamily::SansSerif, 16. * scale, FontStyle::Normal);
+        // Follow the druid theme instead of hard-coding the colors.
+        let text_color = ctx.color(druid::theme::TEXT_COLOR);
+        let border_color = ctx.color(druid::theme::BORDER_DARK);
 
-        let mut chart = ChartBuilder::on(&root)
-            .x_label_area_size(30)
-            .y_label_area_size(30)
-            .margin_right(10)
-            .build_cartesian_2d(0.0..1_f32, 0.0..6_f32)
-            .unwrap();
+        let mut chart = ChartBuilder::on(root)
+            .x_label_area_size(30. * scale)
+            .y_label_area_size(30. * scale)
+            .margin_right(10. * scale)
+            .build_cartesian_2d(0.0..1_f32, 0.0..6_f32)?;
 
         chart
             .configure_mesh()
-            .axis_style(&RGBColor(28, 28, 28))
-            .x_label_style(font.clone().with_color(&WHITE))
-            .y_label_style(font.clone().with_color(&WHITE))
-            .draw()
-            .unwrap();
+            .axis_style(border_color)
+            .x_label_style(font.clone().with_color(text_color))
+            .y_label_style(font.clone().with_color(text_color))
+            .draw()?;
 
         for (σ, idx) in [0.32_f32, 0.56, 1., 1.78, 3.16].into_iter().zip(0..) {
             let fac = 1. / (σ * std::f32::consts::TAU.sqrt());
@@ -42,25 +46,26 @@ fn build_plot_widget() -> impl Widget<State> {
             });
 
             chart
-                .draw_series(LineSeries::new(data, &color))
-                .unwrap()
+                .draw_series(LineSeries::new(data, &color))?
                 .label(format!("σ = {σ}"))
                 .legend(move |(x, y)| {
                     PathElement::new(
-                        vec![(x, y), (x + 20, y)],
-                        ShapeStyle::from(&color).stroke_width(2),
+                        vec![(x, y), (x + (20. * scale) as i32, y)],
+                        ShapeStyle::from(&color).stroke_width((2. * scale).round() as u32),
                     )
                 });
         }
         chart
             .configure_series_labels()
             .position(SeriesLabelPosition::UpperRight)
-            .background_style(&RGBColor(41, 41, 41))
-            .border_style(&RGBColor(28, 28, 28))
-            .label_font(font.with_color(&WHITE))
-            .draw()
-            .unwrap();
+            .background_style(ctx.color(druid::theme::BACKGROUND_LIGHT))
+            .border_style(border_color)
+            .label_font(font.with_color(text_color))
+            .draw()?;
+
+        Ok(())
     })
+    .with_device_pixels(true)
 }
 
 fn build_slider_widget(name: String, min: f64, max: f64) -> impl Widget<f64> {
@@ -69,7 +74,7 @@ fn build_slider_widget(name: String, min: f64, max: f64) -> impl Widget<f64> {
         .with_flex_child(
             Slider::new().with_range(min, max).env_scope(|env, _| {
                 // remove the width limit in [`Slider`]
-                env.set(druid::theme::WIDE_WIDGET_WIDTH, std::f64::INFINITY)
+                env.set(druid::theme::WIDE_WIDGET_WIDTH, f64::INFINITY)
             }),
             1.,
         )
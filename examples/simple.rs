@@ -6,7 +6,7 @@ fn build_plot_widget() -> impl Widget<()> {
     Plot::new(|_size, _data, root| {
         // Code taken from the plotters example: https://github.com/38/plotters#quick-start
         root.fill(&WHITE).unwrap();
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(root)
             .caption("y=x^2", ("sans-serif", 50).into_font())
             .margin(5)
             .margin_right(15)
@@ -20,16 +20,16 @@ fn build_plot_widget() -> impl Widget<()> {
         chart
             .draw_series(LineSeries::new(
                 (-50..=50).map(|x| x as f32 / 50.0).map(|x| (x, x * x)),
-                &RED,
+                RED,
             ))
             .unwrap()
             .label("y = x^2")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
         chart
             .configure_series_labels()
-            .background_style(&WHITE.mix(0.8))
-            .border_style(&BLACK)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
             .draw()
             .unwrap();
     })
@@ -0,0 +1,55 @@
+use druid::{AppLauncher, Widget, WindowDesc};
+use plotters::prelude::*;
+use plotters_druid::Plot;
+
+fn build_plot_widget() -> impl Widget<()> {
+    let temperatures: Vec<(f64, f64)> = (0..24)
+        .map(|hour| hour as f64)
+        .map(|hour| {
+            (
+                hour,
+                14. + 6. * ((hour - 9.) * std::f64::consts::PI / 12.).sin(),
+            )
+        })
+        .collect();
+
+    Plot::interactive(-1.0..24.0, 0.0..25.0, move |ctx, _data, root| {
+        root.fill(&WHITE)?;
+        let scale = ctx.scale();
+        let mut chart = ChartBuilder::on(root)
+            .margin(5. * scale)
+            .margin_right(15. * scale)
+            .x_label_area_size(30. * scale)
+            .y_label_area_size(40. * scale)
+            .build_cartesian_2d(ctx.x_range(), ctx.y_range())?;
+
+        // Needed for zooming around the cursor and for the tooltips to find the points on
+        // screen.
+        ctx.set_coord_mapping(chart.plotting_area());
+        ctx.add_tooltip_series("Temperature", temperatures.iter().copied());
+
+        chart.configure_mesh().draw()?;
+        chart.draw_series(LineSeries::new(temperatures.iter().copied(), &BLUE))?;
+        chart.draw_series(
+            temperatures
+                .iter()
+                .map(|&point| Circle::new(point, 3. * scale, BLUE.filled())),
+        )?;
+
+        Ok(())
+    })
+    .with_device_pixels(true)
+    .with_tooltip_format(|name, (hour, temperature)| {
+        format!("{name}\n{hour:02.0}:00  {temperature:.1} °C")
+    })
+}
+
+fn main() {
+    let main_window = WindowDesc::new(build_plot_widget())
+        .title("Hover a point to see its value")
+        .window_size((600.0, 400.0));
+
+    AppLauncher::with_window(main_window)
+        .launch(())
+        .expect("Failed to launch application");
+}
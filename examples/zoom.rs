@@ -0,0 +1,43 @@
+use druid::{AppLauncher, Widget, WindowDesc};
+use plotters::prelude::*;
+use plotters_druid::Plot;
+
+fn build_plot_widget() -> impl Widget<()> {
+    Plot::interactive(-4.0..4.0, -1.2..1.2, |ctx, _data, root| {
+        root.fill(&WHITE)?;
+        let scale = ctx.scale();
+        let mut chart = ChartBuilder::on(root)
+            .margin(5. * scale)
+            .margin_right(15. * scale)
+            .x_label_area_size(30. * scale)
+            .y_label_area_size(40. * scale)
+            .build_cartesian_2d(ctx.x_range(), ctx.y_range())?;
+
+        // Let the widget know where the chart is, so that zooming follows the cursor.
+        ctx.set_plotting_area(chart.plotting_area());
+
+        chart.configure_mesh().draw()?;
+
+        let x_range = ctx.x_range();
+        let res = 1000;
+        chart.draw_series(LineSeries::new(
+            (0..=res)
+                .map(|i| x_range.start + (x_range.end - x_range.start) * i as f64 / res as f64)
+                .map(|x| (x, (x * 3.).sin() * (-x * x / 8.).exp())),
+            &RED,
+        ))?;
+
+        Ok(())
+    })
+    .with_device_pixels(true)
+}
+
+fn main() {
+    let main_window = WindowDesc::new(build_plot_widget())
+        .title("Scroll to zoom, drag to pan, double-click to reset")
+        .window_size((600.0, 400.0));
+
+    AppLauncher::with_window(main_window)
+        .launch(())
+        .expect("Failed to launch application");
+}
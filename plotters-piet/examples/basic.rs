@@ -13,10 +13,7 @@ fn main() {
     // before we try to save the png at the end.
     {
         let mut render_ctx = bitmap.render_context();
-        let piet_backend = PietBackend {
-            size: (width as u32, height as u32),
-            render_ctx: &mut render_ctx,
-        };
+        let piet_backend = PietBackend::new((width as u32, height as u32), &mut render_ctx);
 
         let root = piet_backend.into_drawing_area();
 
@@ -39,12 +36,12 @@ fn main() {
             ))
             .unwrap()
             .label("y = x^2")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
         chart
             .configure_series_labels()
-            .background_style(&WHITE)
-            .border_style(&BLACK)
+            .background_style(WHITE)
+            .border_style(BLACK)
             .draw()
             .unwrap();
 
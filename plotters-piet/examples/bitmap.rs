@@ -0,0 +1,31 @@
+use plotters::prelude::*;
+use plotters_piet::PietBitmapBackend;
+
+fn main() {
+    let mut backend = PietBitmapBackend::new((1920, 1080)).unwrap();
+
+    let root = (&mut backend).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(&root)
+        .caption("y=sin(x)", ("sans-serif", 50).into_font())
+        .margin(5)
+        .margin_right(15)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(-3f32..3f32, -1.1f32..1.1f32)
+        .unwrap();
+
+    chart.configure_mesh().draw().unwrap();
+    chart
+        .draw_series(LineSeries::new(
+            (-150..=150).map(|x| x as f32 / 50.0).map(|x| (x, x.sin())),
+            &BLUE,
+        ))
+        .unwrap();
+
+    root.present().unwrap();
+    drop(chart);
+    drop(root);
+
+    backend.save_png("plot.png").unwrap();
+}
@@ -0,0 +1,442 @@
+//! A backend that owns the bitmap it draws on.
+
+use piet_common::{BitmapTarget, Device, ImageFormat, Piet, RenderContext, StrokeStyle};
+use plotters_backend::{BackendColor, BackendCoord, DrawingBackend, DrawingErrorKind};
+
+use crate::{Error, PietBackend, PietDrawingBackend};
+
+/// The layout of the pixels that [`PietBitmapBackend::copy_pixels`] writes.
+///
+/// Every format has four bytes per pixel, stored row by row without any padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Red, green, blue and alpha with straight alpha, like `image::RgbaImage`.
+    Rgba,
+    /// Red, green, blue and alpha with the colors premultiplied by alpha.
+    RgbaPremul,
+    /// Blue, green, red and alpha with straight alpha.
+    Bgra,
+}
+
+/// A [`PietBackend`] that draws on its own offscreen bitmap.
+///
+/// This is meant for rendering plots without a window, e.g. on a server. Once the plot is
+/// drawn, take the pixels with [`into_rgba_buffer`](Self::into_rgba_buffer) or write them
+/// to a file with `save_png` (needs the `png` feature).
+///
+/// ```rust
+/// # use plotters::prelude::*;
+/// # use plotters_piet::PietBitmapBackend;
+/// let mut backend = PietBitmapBackend::new((640, 480)).unwrap();
+/// let root = (&mut backend).into_drawing_area();
+/// root.fill(&WHITE).unwrap();
+/// drop(root);
+///
+/// let pixels = backend.into_rgba_buffer().unwrap();
+/// assert_eq!(pixels.len(), 640 * 480 * 4);
+/// ```
+pub struct PietBitmapBackend {
+    // The fields borrow from each other: the backend borrows the render context, which
+    // borrows the bitmap, which borrows the device. The borrowed values are kept on the
+    // heap so that their addresses don't change when this struct moves, and they are
+    // freed in the reverse order in `Drop`. Null pointers are parts that have already
+    // been freed.
+    backend: Option<PietBackend<'static, 'static>>,
+    render_ctx: *mut Piet<'static>,
+    bitmap: *mut BitmapTarget<'static>,
+    device: *mut Device,
+}
+
+impl PietBitmapBackend {
+    /// Create a bitmap of `size` pixels.
+    pub fn new(size: (u32, u32)) -> Result<PietBitmapBackend, Error> {
+        PietBitmapBackend::with_scale(size, 1.)
+    }
+
+    /// Create a bitmap of `size * scale` pixels.
+    ///
+    /// Plotters draws in the pixels of the bitmap, like with [`PietBackend::with_scale`].
+    pub fn with_scale(size: (u32, u32), scale: f64) -> Result<PietBitmapBackend, Error> {
+        let width = (size.0 as f64 * scale).round() as usize;
+        let height = (size.1 as f64 * scale).round() as usize;
+
+        let mut result = PietBitmapBackend {
+            backend: None,
+            render_ctx: std::ptr::null_mut(),
+            bitmap: std::ptr::null_mut(),
+            device: std::ptr::null_mut(),
+        };
+        // If any of the steps fail, `Drop` cleans up the parts that have been created.
+        result.device = Box::into_raw(Box::new(Device::new().map_err(piet_error)?));
+        // SAFETY: The device stays valid until `Drop`, which frees the bitmap first.
+        let bitmap = unsafe { &mut *result.device }
+            .bitmap_target(width, height, scale)
+            .map_err(piet_error)?;
+        result.bitmap = Box::into_raw(Box::new(bitmap));
+        result.start(size, scale);
+        Ok(result)
+    }
+
+    /// The number of bitmap pixels per unit of the size that the backend was created with.
+    pub fn scale(&self) -> f64 {
+        self.backend().scale()
+    }
+
+    /// Builder-style method to set the stroke style, see [`PietBackend::set_stroke_style`].
+    pub fn with_stroke_style(mut self, style: StrokeStyle) -> Self {
+        self.set_stroke_style(style);
+        self
+    }
+
+    /// Set the caps, joins and dashes of lines and outlines, see
+    /// [`PietBackend::set_stroke_style`].
+    pub fn set_stroke_style(&mut self, style: StrokeStyle) {
+        self.backend_mut().set_stroke_style(style);
+    }
+
+    /// Finish drawing and return the pixels of the bitmap.
+    ///
+    /// The pixels are in RGBA order with straight (not premultiplied) alpha, row by row
+    /// without any padding.
+    pub fn into_rgba_buffer(mut self) -> Result<Vec<u8>, Error> {
+        let (width, height) = self.get_size();
+        let mut buf = vec![0; width as usize * height as usize * 4];
+        self.copy_pixels(PixelFormat::Rgba, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Copy what has been drawn so far into `buf`, in the given pixel format.
+    ///
+    /// `buf` has to hold at least `width * height * 4` bytes, where the size is the one
+    /// returned by [`get_size`](DrawingBackend::get_size). An `image::RgbaImage` of that
+    /// size can be passed with `&mut image` and [`PixelFormat::Rgba`].
+    ///
+    /// Drawing can go on afterwards, e.g. to render the next frame of a video on top of
+    /// the current one.
+    pub fn copy_pixels(&mut self, format: PixelFormat, buf: &mut [u8]) -> Result<(), Error> {
+        let (width, height) = self.get_size();
+        let required = width as usize * height as usize * 4;
+        if buf.len() < required {
+            return Err(Error::BufferTooSmall {
+                required,
+                len: buf.len(),
+            });
+        }
+        let buf = &mut buf[..required];
+
+        let size = self.backend().size;
+        let scale = self.scale();
+        let stroke_style = self.backend().stroke_style().clone();
+        let result = self.finish().and_then(|bitmap| {
+            bitmap
+                .copy_raw_pixels(ImageFormat::RgbaPremul, buf)
+                .map_err(piet_error)
+        });
+        self.start(size, scale);
+        self.set_stroke_style(stroke_style);
+        result?;
+
+        convert_premul_rgba(buf, format);
+        Ok(())
+    }
+
+    /// Finish drawing and write the bitmap to a PNG file.
+    #[cfg(feature = "png")]
+    pub fn save_png(mut self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        self.finish()?;
+        // SAFETY: The render context is gone, so nothing borrows the bitmap anymore. The
+        // null pointer keeps `Drop` from freeing it again.
+        let bitmap =
+            unsafe { Box::from_raw(std::mem::replace(&mut self.bitmap, std::ptr::null_mut())) };
+        bitmap.save_to_file(path).map_err(piet_error)
+    }
+
+    fn backend(&self) -> &PietBackend<'static, 'static> {
+        self.backend
+            .as_ref()
+            .expect("backend is only missing while the bitmap is read")
+    }
+
+    fn backend_mut(&mut self) -> &mut PietBackend<'static, 'static> {
+        self.backend
+            .as_mut()
+            .expect("backend is only missing while the bitmap is read")
+    }
+
+    /// Create the render context and the backend for the bitmap.
+    fn start(&mut self, size: (u32, u32), scale: f64) {
+        // SAFETY: The bitmap stays valid until `Drop`, which frees the render context and
+        // the backend first.
+        unsafe {
+            self.render_ctx = Box::into_raw(Box::new((*self.bitmap).render_context()));
+            self.backend = Some(PietBackend::with_scale(size, scale, &mut *self.render_ctx));
+        }
+    }
+
+    /// Free the backend and the render context, so that the bitmap can be read.
+    fn finish(&mut self) -> Result<&mut BitmapTarget<'static>, Error> {
+        self.backend = None;
+        // SAFETY: Nothing borrows from the render context anymore, and the bitmap is only
+        // borrowed through `self`.
+        unsafe {
+            let mut render_ctx = Box::from_raw(self.render_ctx);
+            self.render_ctx = std::ptr::null_mut();
+            render_ctx.finish().map_err(piet_error)?;
+            drop(render_ctx);
+            Ok(&mut *self.bitmap)
+        }
+    }
+}
+
+impl Drop for PietBitmapBackend {
+    fn drop(&mut self) {
+        self.backend = None;
+        // SAFETY: The pointers are either null or came from `Box::into_raw`, and every part
+        // is freed before the one it borrows from.
+        unsafe {
+            if !self.render_ctx.is_null() {
+                let mut render_ctx = Box::from_raw(self.render_ctx);
+                // Some piet backends complain if the render context isn't finished.
+                let _ = render_ctx.finish();
+            }
+            if !self.bitmap.is_null() {
+                drop(Box::from_raw(self.bitmap));
+            }
+            if !self.device.is_null() {
+                drop(Box::from_raw(self.device));
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for PietBitmapBackend {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("PietBitmapBackend")
+            .field("size", &self.get_size())
+            .finish()
+    }
+}
+
+/// Implement `DrawingBackend` by passing every call on to the `PietBackend`.
+///
+/// We need this for both `PietBitmapBackend` and `&mut PietBitmapBackend`, the latter is
+/// what you draw with if you want to read the bitmap afterwards.
+macro_rules! delegate_drawing_backend {
+    ($($impl:tt)*) => {
+        $($impl)* {
+            type ErrorType = Error;
+
+            fn get_size(&self) -> (u32, u32) {
+                self.backend().get_size()
+            }
+
+            fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().ensure_prepared()
+            }
+
+            fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().present()
+            }
+
+            fn draw_pixel(
+                &mut self,
+                point: BackendCoord,
+                color: BackendColor,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().draw_pixel(point, color)
+            }
+
+            fn draw_line<S: plotters_backend::BackendStyle>(
+                &mut self,
+                from: BackendCoord,
+                to: BackendCoord,
+                style: &S,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().draw_line(from, to, style)
+            }
+
+            fn draw_rect<S: plotters_backend::BackendStyle>(
+                &mut self,
+                upper_left: BackendCoord,
+                bottom_right: BackendCoord,
+                style: &S,
+                fill: bool,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut()
+                    .draw_rect(upper_left, bottom_right, style, fill)
+            }
+
+            fn draw_path<S: plotters_backend::BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+                &mut self,
+                path: I,
+                style: &S,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().draw_path(path, style)
+            }
+
+            fn draw_circle<S: plotters_backend::BackendStyle>(
+                &mut self,
+                center: BackendCoord,
+                radius: u32,
+                style: &S,
+                fill: bool,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().draw_circle(center, radius, style, fill)
+            }
+
+            fn fill_polygon<S: plotters_backend::BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+                &mut self,
+                vert: I,
+                style: &S,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().fill_polygon(vert, style)
+            }
+
+            fn draw_text<TStyle: plotters_backend::BackendTextStyle>(
+                &mut self,
+                text: &str,
+                style: &TStyle,
+                pos: BackendCoord,
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().draw_text(text, style, pos)
+            }
+
+            fn estimate_text_size<TStyle: plotters_backend::BackendTextStyle>(
+                &self,
+                text: &str,
+                style: &TStyle,
+            ) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+                self.backend().estimate_text_size(text, style)
+            }
+
+            fn blit_bitmap(
+                &mut self,
+                pos: BackendCoord,
+                size: (u32, u32),
+                src: &[u8],
+            ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+                self.backend_mut().blit_bitmap(pos, size, src)
+            }
+        }
+    };
+}
+
+delegate_drawing_backend!(impl DrawingBackend for PietBitmapBackend);
+delegate_drawing_backend!(impl DrawingBackend for &mut PietBitmapBackend);
+
+impl PietDrawingBackend for PietBitmapBackend {
+    fn with_piet_backend<T>(&mut self, draw: impl FnOnce(&mut PietBackend<'_, '_>) -> T) -> T {
+        draw(self.backend_mut())
+    }
+}
+
+impl PietDrawingBackend for &mut PietBitmapBackend {
+    fn with_piet_backend<T>(&mut self, draw: impl FnOnce(&mut PietBackend<'_, '_>) -> T) -> T {
+        draw(self.backend_mut())
+    }
+}
+
+fn piet_error(e: piet_common::Error) -> Error {
+    Error::Render(e.into())
+}
+
+/// Convert premultiplied RGBA pixels to the given format in place.
+fn convert_premul_rgba(pixels: &mut [u8], format: PixelFormat) {
+    match format {
+        PixelFormat::Rgba => unpremultiply(pixels),
+        PixelFormat::RgbaPremul => {}
+        PixelFormat::Bgra => {
+            unpremultiply(pixels);
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+    }
+}
+
+/// Convert premultiplied pixels to straight alpha in place.
+fn unpremultiply(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn draw_into_owned_bitmap() {
+        let mut backend = PietBitmapBackend::with_scale((3, 2), 2.).unwrap();
+        assert_eq!(backend.get_size(), (6, 4));
+
+        let root = (&mut backend).into_drawing_area();
+        root.fill(&RED.mix(0.5)).unwrap();
+        root.draw_pixel((0, 0), &BLUE).unwrap();
+        drop(root);
+
+        let buf = backend.into_rgba_buffer().unwrap();
+        assert_eq!(buf.len(), 6 * 4 * 4);
+        assert_eq!(buf[..4], [0, 0, 255, 255]);
+        for pixel in buf.chunks(4).skip(1) {
+            assert_eq!(pixel[0], 255);
+            assert_eq!(pixel[1..3], [0, 0]);
+            assert!(pixel[3].abs_diff(128) <= 1);
+        }
+    }
+
+    #[test]
+    fn copy_pixels_and_continue() {
+        let mut backend = PietBitmapBackend::new((2, 1)).unwrap();
+        backend
+            .draw_pixel((0, 0), RED.mix(0.5).to_backend_color())
+            .unwrap();
+
+        let mut buf = [0; 8];
+        backend
+            .copy_pixels(PixelFormat::RgbaPremul, &mut buf)
+            .unwrap();
+        assert_eq!(buf, [128, 0, 0, 128, 0, 0, 0, 0]);
+        backend.copy_pixels(PixelFormat::Rgba, &mut buf).unwrap();
+        assert_eq!(buf, [255, 0, 0, 128, 0, 0, 0, 0]);
+        backend.copy_pixels(PixelFormat::Bgra, &mut buf).unwrap();
+        assert_eq!(buf, [0, 0, 255, 128, 0, 0, 0, 0]);
+
+        backend.draw_pixel((1, 0), BLUE.to_backend_color()).unwrap();
+        let buf = backend.into_rgba_buffer().unwrap();
+        assert_eq!(buf, [255, 0, 0, 128, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn copy_pixels_buffer_too_small() {
+        let mut backend = PietBitmapBackend::new((2, 2)).unwrap();
+        let result = backend.copy_pixels(PixelFormat::Rgba, &mut [0; 15]);
+        assert_eq!(
+            result,
+            Err(Error::BufferTooSmall {
+                required: 16,
+                len: 15
+            })
+        );
+    }
+
+    #[test]
+    fn drop_without_drawing() {
+        let backend = PietBitmapBackend::new((10, 10)).unwrap();
+        drop(backend);
+    }
+
+    #[test]
+    fn unpremultiply_alpha() {
+        let mut pixels = [0, 0, 0, 0, 50, 100, 0, 100, 255, 255, 255, 255];
+        unpremultiply(&mut pixels);
+        assert_eq!(pixels, [0, 0, 0, 0, 128, 255, 0, 100, 255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,284 @@
+//! Plotters elements that use features of piet which the plotters backend API doesn't
+//! cover.
+
+use piet_common::{
+    kurbo::{self, Shape},
+    StrokeStyle,
+};
+use plotters::element::{CoordMapper, Drawable, PointCollection};
+use plotters::style::ShapeStyle;
+use plotters_backend::DrawingErrorKind;
+
+use crate::precise::PreciseDrawable;
+use crate::{Error, PietBackend, PietDrawingBackend};
+
+/// An element that is drawn with its own stroke style.
+///
+/// The caps, joins and dashes of the lines and outlines of `element` are taken from
+/// `style`, everything else is drawn with the stroke style of the backend, see
+/// [`PietBackend::set_stroke_style`].
+///
+/// ```rust
+/// # use piet_common::RenderContext;
+/// # use plotters::prelude::*;
+/// # use plotters_piet::PietBackend;
+/// use piet_common::StrokeStyle;
+/// use plotters_piet::WithStrokeStyle;
+/// # let mut device = piet_common::Device::new().unwrap();
+/// # let mut bitmap = device.bitmap_target(640, 480, 1.0).unwrap();
+/// # let mut render_ctx = bitmap.render_context();
+/// # let root = PietBackend::new((640, 480), &mut render_ctx).into_drawing_area();
+///
+/// // A dashed threshold line.
+/// let line = PathElement::new([(0, 240), (640, 240)], RED);
+/// let dashed = StrokeStyle::new().dash_pattern(&[6., 4.]);
+/// root.draw(&WithStrokeStyle::new(line, dashed)).unwrap();
+/// # drop(root);
+/// # render_ctx.finish().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WithStrokeStyle<E> {
+    element: E,
+    style: StrokeStyle,
+}
+
+impl<E> WithStrokeStyle<E> {
+    /// Draw `element` with the given stroke style.
+    pub fn new(element: E, style: StrokeStyle) -> WithStrokeStyle<E> {
+        WithStrokeStyle { element, style }
+    }
+}
+
+impl<'a, Coord, CM, E> PointCollection<'a, Coord, CM> for &'a WithStrokeStyle<E>
+where
+    &'a E: PointCollection<'a, Coord, CM>,
+{
+    type Point = <&'a E as PointCollection<'a, Coord, CM>>::Point;
+    type IntoIter = <&'a E as PointCollection<'a, Coord, CM>>::IntoIter;
+
+    fn point_iter(self) -> Self::IntoIter {
+        (&self.element).point_iter()
+    }
+}
+
+impl<DB, E, CM> Drawable<DB, CM> for WithStrokeStyle<E>
+where
+    DB: PietDrawingBackend,
+    E: Drawable<DB, CM>,
+    CM: CoordMapper,
+{
+    fn draw<I: Iterator<Item = CM::Output>>(
+        &self,
+        pos: I,
+        backend: &mut DB,
+        parent_dim: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        let previous = backend.with_piet_backend(|backend| {
+            let previous = backend.stroke_style().clone();
+            backend.set_stroke_style(self.style.clone());
+            previous
+        });
+        let result = self.element.draw(pos, backend, parent_dim);
+        backend.with_piet_backend(|backend| backend.set_stroke_style(previous));
+        result
+    }
+}
+
+impl<Coord, E: PreciseDrawable<Coord>> PreciseDrawable<Coord> for WithStrokeStyle<E> {
+    fn draw_precise(
+        &self,
+        map: &dyn Fn(&Coord) -> kurbo::Point,
+        backend: &mut PietBackend,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        let previous = backend.stroke_style().clone();
+        backend.set_stroke_style(self.style.clone());
+        let result = self.element.draw_precise(map, backend);
+        backend.set_stroke_style(previous);
+        result
+    }
+}
+
+/// Any kurbo shape, e.g. a Bézier curve, an arc or a rounded rectangle, given in the
+/// coordinates of a chart.
+///
+/// The points of the shape are mapped by the coordinate system one by one, so circles
+/// become ellipses when the axes have different scales. On axes that aren't linear, only
+/// the points on the curves end up in the right place.
+///
+/// This is drawn with [`DrawPrecise::draw_precise`], the fill first and then the outline
+/// with the stroke style of the backend.
+///
+/// ```rust
+/// # use plotters::prelude::*;
+/// # use plotters_piet::PietBitmapBackend;
+/// use piet_common::kurbo::{CubicBez, RoundedRect};
+/// use plotters_piet::{precise::DrawPrecise, KurboShape};
+///
+/// let backend = PietBitmapBackend::new((640, 480)).unwrap();
+/// let root = backend.into_drawing_area();
+/// let chart = ChartBuilder::on(&root)
+///     .build_cartesian_2d(0.0..10.0, 0.0..10.0)
+///     .unwrap();
+///
+/// let highlight = RoundedRect::new(2., 2., 5., 6., 0.5);
+/// chart
+///     .draw_precise(&KurboShape::new(highlight).with_fill(BLUE.mix(0.2)))
+///     .unwrap();
+/// let curve = CubicBez::new((0., 0.), (3., 10.), (7., 0.), (10., 10.));
+/// chart
+///     .draw_precise(&KurboShape::new(curve).with_stroke(RED.stroke_width(2)))
+///     .unwrap();
+/// ```
+///
+/// [`DrawPrecise::draw_precise`]: crate::precise::DrawPrecise::draw_precise
+#[derive(Clone)]
+pub struct KurboShape {
+    path: kurbo::BezPath,
+    fill: Option<ShapeStyle>,
+    stroke: Option<ShapeStyle>,
+}
+
+impl KurboShape {
+    /// Create an element for `shape`, which is neither filled nor stroked yet.
+    pub fn new(shape: impl Shape) -> KurboShape {
+        // The tolerance has to be given in chart coordinates, which can have any scale.
+        let size = shape.bounding_box().size();
+        let tolerance =
+            (size.width.max(size.height) * RELATIVE_SHAPE_TOLERANCE).max(f64::MIN_POSITIVE);
+        KurboShape {
+            path: shape.into_path(tolerance),
+            fill: None,
+            stroke: None,
+        }
+    }
+
+    /// Fill the shape with the color of `style`.
+    pub fn with_fill<S: Into<ShapeStyle>>(mut self, style: S) -> Self {
+        self.fill = Some(style.into());
+        self
+    }
+
+    /// Stroke the outline of the shape with the color and width of `style`.
+    pub fn with_stroke<S: Into<ShapeStyle>>(mut self, style: S) -> Self {
+        self.stroke = Some(style.into());
+        self
+    }
+}
+
+/// How far the curves of a [`KurboShape`] may be off when it is turned into a path,
+/// relative to its size.
+const RELATIVE_SHAPE_TOLERANCE: f64 = 1e-4;
+
+impl PreciseDrawable<(f64, f64)> for KurboShape {
+    fn draw_precise(
+        &self,
+        map: &dyn Fn(&(f64, f64)) -> kurbo::Point,
+        backend: &mut PietBackend,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        let map = |point: kurbo::Point| map(&(point.x, point.y));
+        let path: kurbo::BezPath = self
+            .path
+            .iter()
+            .map(|el| match el {
+                kurbo::PathEl::MoveTo(p) => kurbo::PathEl::MoveTo(map(p)),
+                kurbo::PathEl::LineTo(p) => kurbo::PathEl::LineTo(map(p)),
+                kurbo::PathEl::QuadTo(p1, p2) => kurbo::PathEl::QuadTo(map(p1), map(p2)),
+                kurbo::PathEl::CurveTo(p1, p2, p3) => {
+                    kurbo::PathEl::CurveTo(map(p1), map(p2), map(p3))
+                }
+                kurbo::PathEl::ClosePath => kurbo::PathEl::ClosePath,
+            })
+            .collect();
+
+        if let Some(fill) = &self.fill {
+            backend.fill_shape(&path, fill)?;
+        }
+        if let Some(stroke) = &self.stroke {
+            backend.stroke_shape(&path, stroke)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::precise::DrawPrecise;
+    use crate::PietBitmapBackend;
+    use piet_common::LineCap;
+    use plotters::coord::types::RangedCoordf64;
+    use plotters::prelude::*;
+
+    #[test]
+    fn with_stroke_style_on_bitmap_backend() {
+        let mut backend = PietBitmapBackend::new((10, 2)).unwrap();
+        {
+            let root = (&mut backend).into_drawing_area();
+            let butt = StrokeStyle::new().line_cap(LineCap::Butt);
+            let line = PathElement::new([(2, 0), (7, 0)], BLACK);
+            root.draw(&WithStrokeStyle::new(line, butt)).unwrap();
+            // The default style is used again afterwards.
+            root.draw(&PathElement::new([(2, 1), (7, 1)], BLACK))
+                .unwrap();
+        }
+        let pixels = backend.into_rgba_buffer().unwrap();
+        let alpha = |x: usize, y: usize| pixels[(y * 10 + x) * 4 + 3];
+
+        // Butt caps end in the middle of the end pixels, square caps cover them.
+        assert!(alpha(2, 0).abs_diff(128) <= 2);
+        assert!(alpha(7, 0).abs_diff(128) <= 2);
+        assert_eq!(alpha(4, 0), 255);
+        assert_eq!(alpha(2, 1), 255);
+        assert_eq!(alpha(7, 1), 255);
+    }
+
+    #[test]
+    fn kurbo_shape_in_chart_coordinates() {
+        let mut backend = PietBitmapBackend::new((20, 10)).unwrap();
+        {
+            let coord: Cartesian2d<RangedCoordf64, RangedCoordf64> =
+                Cartesian2d::new(0.0..2.0, 0.0..1.0, (0..20, 0..10));
+            let area = (&mut backend).into_drawing_area().apply_coord_spec(coord);
+            let rect = kurbo::Rect::new(0.5, 0.2, 1.0, 0.8);
+            area.draw_precise(&KurboShape::new(rect).with_fill(RED))
+                .unwrap();
+        }
+        let pixels = backend.into_rgba_buffer().unwrap();
+        let pixel = |x: usize, y: usize| &pixels[(y * 20 + x) * 4..(y * 20 + x) * 4 + 4];
+
+        // The rectangle reaches from the center of pixel (5, 2) to the one of (10, 8).
+        assert_eq!(pixel(7, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(4, 5)[3], 0);
+        assert_eq!(pixel(11, 5)[3], 0);
+        assert_eq!(pixel(7, 1)[3], 0);
+        assert_eq!(pixel(7, 9)[3], 0);
+    }
+
+    #[test]
+    fn kurbo_shape_in_chart_with_y_axis_up() {
+        let mut backend = PietBitmapBackend::new((20, 10)).unwrap();
+        {
+            let root = (&mut backend).into_drawing_area();
+            let chart = ChartBuilder::on(&root)
+                .build_cartesian_2d(0.0..2.0, 0.0..1.0)
+                .unwrap();
+            // The same pixels that plotters maps the corners to.
+            assert_eq!(chart.as_coord_spec().translate(&(0.5, 0.8)), (5, 1));
+            assert_eq!(chart.as_coord_spec().translate(&(1.0, 0.2)), (10, 7));
+
+            let rect = kurbo::Rect::new(0.5, 0.2, 1.0, 0.8);
+            chart
+                .draw_precise(&KurboShape::new(rect).with_fill(RED))
+                .unwrap();
+        }
+        let pixels = backend.into_rgba_buffer().unwrap();
+        let pixel = |x: usize, y: usize| &pixels[(y * 20 + x) * 4..(y * 20 + x) * 4 + 4];
+
+        // The rectangle reaches from the center of pixel (5, 1) to the one of (10, 7).
+        assert_eq!(pixel(7, 4), [255, 0, 0, 255]);
+        assert!(pixel(7, 1)[3] > 0 && pixel(7, 1)[3] < 255);
+        assert!(pixel(7, 7)[3] > 0 && pixel(7, 7)[3] < 255);
+        assert_eq!(pixel(7, 0)[3], 0);
+        assert_eq!(pixel(7, 8)[3], 0);
+    }
+}
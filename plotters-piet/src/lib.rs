@@ -1,33 +1,387 @@
 /*!
 A [Piet](https://crates.io/crates/piet) backend for [Plotters](https://crates.io/crates/plotters). This lets you draw plots on a Piet render context.
+
+With the `svg` feature, the same [`PietBackend`] can also draw on the render context of
+[piet-svg](https://crates.io/crates/piet-svg), see [`PietBackend::new_svg`].
+
+For drawing without a window, [`PietBitmapBackend`] owns the bitmap that it draws on.
+
+With the `pdf` feature, plots can be drawn into multi-page PDF files through cairo, see
+`PdfDocument`. This is only available on Linux and the BSDs, where piet uses cairo.
+
+Plotters rounds every coordinate to a whole pixel. The elements in [`precise`] are drawn
+with fractional pixel coordinates instead, for smooth lines and scatter points. Shapes
+that plotters has no element for, like Bézier curves, arcs or rounded rectangles, can be
+drawn in chart coordinates with [`KurboShape`].
 */
 
-use piet_common::{kurbo, Color, LineCap, Piet, RenderContext, StrokeStyle};
-use plotters_backend::{BackendColor, BackendCoord, DrawingBackend, DrawingErrorKind};
+use piet_common::{
+    kurbo::{self, Shape},
+    Color, FontFamily, FontStyle, FontWeight, ImageFormat, InterpolationMode, LineCap, LineJoin,
+    Piet, RenderContext, StrokeStyle, Text, TextAttribute, TextLayout, TextLayoutBuilder,
+};
+use plotters_backend::{
+    text_anchor::{HPos, VPos},
+    BackendColor, BackendCoord, DrawingBackend, DrawingErrorKind, FontTransform,
+};
+
+mod bitmap;
+mod element;
+#[cfg(all(
+    feature = "pdf",
+    any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    )
+))]
+mod pdf;
+pub mod precise;
+
+pub use bitmap::{PietBitmapBackend, PixelFormat};
+pub use element::{KurboShape, WithStrokeStyle};
+#[cfg(all(
+    feature = "pdf",
+    any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    )
+))]
+pub use pdf::PdfDocument;
+
+/// The piet-svg crate that [`PietBackend::new_svg`] works with.
+#[cfg(feature = "svg")]
+pub use piet_svg;
 
+/// The error type of [`PietBackend`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct Error {}
+#[non_exhaustive]
+pub enum Error {
+    /// Piet doesn't know the font family with the given name.
+    MissingFont(String),
+    /// Piet couldn't lay out a piece of text.
+    TextLayout(PietError),
+    /// The pixel data passed to `blit_bitmap` couldn't be turned into a piet image.
+    InvalidImage(PietError),
+    /// The piet render context reported an error, e.g. from the platform backend.
+    Render(PietError),
+    /// The buffer passed to [`PietBitmapBackend::copy_pixels`] can't hold all the pixels.
+    BufferTooSmall { required: usize, len: usize },
+}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "plotters-piet error")
+        match self {
+            Error::MissingFont(name) => write!(f, "font family \"{name}\" not found"),
+            Error::TextLayout(_) => write!(f, "failed to lay out text"),
+            Error::InvalidImage(_) => write!(f, "failed to create image"),
+            Error::Render(_) => write!(f, "failed to render"),
+            Error::BufferTooSmall { required, len } => {
+                write!(
+                    f,
+                    "buffer of {len} bytes is too small, {required} bytes required"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::MissingFont(_) | Error::BufferTooSmall { .. } => None,
+            Error::TextLayout(e) | Error::InvalidImage(e) | Error::Render(e) => Some(e),
+        }
+    }
+}
+
+/// An error reported by piet.
+///
+/// Plotters requires backend errors to be `Send` and `Sync`, which [`piet_common::Error`]
+/// isn't because it may wrap an error of the platform backend. This is why we keep a copy
+/// of its kind, see [`PietError::kind`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PietError {
+    kind: PietErrorKind,
+}
+
+impl PietError {
+    /// What went wrong, like the variant of the original [`piet_common::Error`].
+    pub fn kind(&self) -> &PietErrorKind {
+        &self.kind
+    }
+}
+
+impl std::fmt::Display for PietError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            PietErrorKind::InvalidInput => write!(f, "invalid input"),
+            PietErrorKind::NotSupported => write!(f, "not supported on the current backend"),
+            PietErrorKind::Unimplemented => write!(f, "not implemented for the current backend"),
+            PietErrorKind::MissingFeature(feature) => write!(f, "missing feature '{feature}'"),
+            PietErrorKind::StackUnbalance => write!(f, "stack unbalanced"),
+            PietErrorKind::BackendError(message) => write!(f, "backend error: {message}"),
+            PietErrorKind::MissingFont => write!(f, "a font could not be found"),
+            PietErrorKind::FontLoadingFailed => write!(f, "a font could not be loaded"),
+            PietErrorKind::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for PietError {}
+
+impl From<piet_common::Error> for PietError {
+    fn from(e: piet_common::Error) -> PietError {
+        let kind = match e {
+            piet_common::Error::InvalidInput => PietErrorKind::InvalidInput,
+            piet_common::Error::NotSupported => PietErrorKind::NotSupported,
+            piet_common::Error::Unimplemented => PietErrorKind::Unimplemented,
+            piet_common::Error::MissingFeature(feature) => PietErrorKind::MissingFeature(feature),
+            piet_common::Error::StackUnbalance => PietErrorKind::StackUnbalance,
+            piet_common::Error::BackendError(e) => PietErrorKind::BackendError(e.to_string()),
+            piet_common::Error::MissingFont => PietErrorKind::MissingFont,
+            piet_common::Error::FontLoadingFailed => PietErrorKind::FontLoadingFailed,
+            e => PietErrorKind::Other(e.to_string()),
+        };
+        PietError { kind }
     }
 }
 
-impl std::error::Error for Error {}
+/// The kind of a [`PietError`], mirroring the variants of [`piet_common::Error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PietErrorKind {
+    /// A function was passed an invalid input.
+    InvalidInput,
+    /// Something is impossible on the current platform.
+    NotSupported,
+    /// Something is possible, but not yet implemented.
+    Unimplemented,
+    /// Piet was compiled without a required feature.
+    MissingFeature(&'static str),
+    /// A stack pop failed.
+    StackUnbalance,
+    /// The platform backend failed, with the message of its error.
+    BackendError(String),
+    /// A font could not be found.
+    MissingFont,
+    /// Font data could not be loaded.
+    FontLoadingFailed,
+    /// An error that piet added after this crate was written, with its message.
+    Other(String),
+}
+
+fn render_error(e: piet_common::Error) -> DrawingErrorKind<Error> {
+    DrawingErrorKind::DrawingError(Error::Render(e.into()))
+}
 
 /// The piet backend.
 ///
 /// Note that the size of the piet context has to be specified here.
+///
+/// If the render context is scaled, e.g. because druid draws in logical pixels on a HiDPI
+/// display, use [`PietBackend::with_scale`] so that plotters draws in device pixels.
+///
+/// ```rust
+/// # use piet_common::RenderContext;
+/// # use plotters_piet::PietBackend;
+/// # let mut device = piet_common::Device::new().unwrap();
+/// # let mut bitmap = device.bitmap_target(640, 480, 1.0).unwrap();
+/// # let mut render_ctx = bitmap.render_context();
+/// let piet_backend = PietBackend::new((640, 480), &mut render_ctx);
+/// # drop(piet_backend);
+/// # render_ctx.finish().unwrap();
+/// ```
 pub struct PietBackend<'a, 'b> {
     pub size: (u32, u32),
-    pub render_ctx: &'a mut Piet<'b>,
+    target: Target<'a, 'b>,
+}
+
+/// The render contexts that we can draw on.
+///
+/// This is an enum rather than a type parameter of [`PietBackend`], so that the same
+/// plotting code can draw on all of them.
+enum Target<'a, 'b> {
+    Piet(RenderBackend<'a, Piet<'b>>),
+    #[cfg(feature = "svg")]
+    Svg(RenderBackend<'a, piet_svg::RenderContext>),
+}
+
+/// Run the same code on whichever render context the backend draws on.
+macro_rules! dispatch {
+    ($target:expr, $backend:ident => $body:expr) => {
+        match $target {
+            Target::Piet($backend) => $body,
+            #[cfg(feature = "svg")]
+            Target::Svg($backend) => $body,
+        }
+    };
+}
+
+impl<'a, 'b> PietBackend<'a, 'b> {
+    /// Create a backend that draws on `render_ctx`, which is `size` pixels large.
+    pub fn new(size: (u32, u32), render_ctx: &'a mut Piet<'b>) -> PietBackend<'a, 'b> {
+        PietBackend::with_scale(size, 1., render_ctx)
+    }
+
+    /// Create a backend that draws on a render context with `scale` device pixels per unit.
+    ///
+    /// `size` is the size of the render context in its own units. Plotters gets to see a
+    /// drawing area of `size * scale` pixels, and everything it draws is scaled down by
+    /// `scale` again, so that one plotters pixel ends up as one device pixel. This way
+    /// stroke widths, font sizes and bitmaps aren't blown up on HiDPI displays.
+    pub fn with_scale(
+        size: (u32, u32),
+        scale: f64,
+        render_ctx: &'a mut Piet<'b>,
+    ) -> PietBackend<'a, 'b> {
+        PietBackend {
+            size,
+            target: Target::Piet(RenderBackend::new(render_ctx, scale)),
+        }
+    }
+
+    /// Create a backend that draws on a piet SVG render context of `size` pixels.
+    ///
+    /// Plotting code that is written against `PietBackend` can this way produce vector
+    /// graphics without any changes.
+    ///
+    /// ```rust
+    /// # use plotters::prelude::*;
+    /// # use plotters_piet::PietBackend;
+    /// let mut render_ctx = piet_svg::RenderContext::new((640., 480.).into());
+    /// let root = PietBackend::new_svg((640, 480), &mut render_ctx).into_drawing_area();
+    /// root.fill(&WHITE).unwrap();
+    /// drop(root);
+    ///
+    /// let mut svg = Vec::new();
+    /// render_ctx.write(&mut svg).unwrap();
+    /// ```
+    #[cfg(feature = "svg")]
+    pub fn new_svg(
+        size: (u32, u32),
+        render_ctx: &'a mut piet_svg::RenderContext,
+    ) -> PietBackend<'a, 'b> {
+        PietBackend {
+            size,
+            target: Target::Svg(RenderBackend::new(render_ctx, 1.)),
+        }
+    }
+
+    /// The number of device pixels per unit of the render context.
+    pub fn scale(&self) -> f64 {
+        dispatch!(&self.target, backend => backend.scale)
+    }
+
+    /// Builder-style method to set the stroke style, see [`set_stroke_style`].
+    ///
+    /// [`set_stroke_style`]: PietBackend::set_stroke_style
+    pub fn with_stroke_style(mut self, style: StrokeStyle) -> Self {
+        self.set_stroke_style(style);
+        self
+    }
+
+    /// Set the caps, joins and dashes of lines, paths and the outlines of rectangles and
+    /// circles. The width comes from the plotters style.
+    ///
+    /// This defaults to [`DEFAULT_STROKE_STYLE`]. Use [`WithStrokeStyle`] to change the
+    /// style of a single element.
+    ///
+    /// ```rust
+    /// # use piet_common::RenderContext;
+    /// # use plotters_piet::PietBackend;
+    /// use piet_common::{LineJoin, StrokeStyle};
+    /// # let mut device = piet_common::Device::new().unwrap();
+    /// # let mut bitmap = device.bitmap_target(640, 480, 1.0).unwrap();
+    /// # let mut render_ctx = bitmap.render_context();
+    /// let piet_backend = PietBackend::new((640, 480), &mut render_ctx)
+    ///     .with_stroke_style(StrokeStyle::new().line_join(LineJoin::Round));
+    /// # drop(piet_backend);
+    /// # render_ctx.finish().unwrap();
+    /// ```
+    pub fn set_stroke_style(&mut self, style: StrokeStyle) {
+        dispatch!(&mut self.target, backend => backend.stroke_style = style)
+    }
+
+    /// The stroke style that is currently used, see [`set_stroke_style`].
+    ///
+    /// [`set_stroke_style`]: PietBackend::set_stroke_style
+    pub fn stroke_style(&self) -> &StrokeStyle {
+        dispatch!(&self.target, backend => &backend.stroke_style)
+    }
+
+    /// Stroke a kurbo shape with the color and width of `style` and the current stroke
+    /// style.
+    ///
+    /// Unlike the plotters drawing methods, this takes coordinates with fractional parts.
+    /// They are in the same pixels as the plotters coordinates, so whole numbers are the
+    /// centers of pixels.
+    pub fn stroke_shape<S: plotters_backend::BackendStyle>(
+        &mut self,
+        shape: impl Shape,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        dispatch!(&mut self.target, backend => backend.stroke_shape(shape, style))
+    }
+
+    /// Fill a kurbo shape with the color of `style`, see [`stroke_shape`] for the
+    /// coordinates.
+    ///
+    /// [`stroke_shape`]: PietBackend::stroke_shape
+    pub fn fill_shape<S: plotters_backend::BackendStyle>(
+        &mut self,
+        shape: impl Shape,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        dispatch!(&mut self.target, backend => backend.fill_shape(shape, style))
+    }
+
+    /// Run `draw` with everything outside of `rect` clipped away. Whole numbers are the
+    /// corners of pixels here.
+    pub(crate) fn clipped(
+        &mut self,
+        rect: kurbo::Rect,
+        draw: impl FnOnce(&mut Self) -> Result<(), DrawingErrorKind<Error>>,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        dispatch!(&mut self.target, backend => backend.push_clip(rect))?;
+        let result = draw(self);
+        let popped = dispatch!(&mut self.target, backend => backend.pop_clip());
+        result.and(popped)
+    }
+}
+
+/// The backends that draw through a [`PietBackend`], i.e. the backend itself and
+/// [`PietBitmapBackend`].
+///
+/// The elements of this crate that need more than the plotters backend API can be drawn
+/// on all of them. This trait can't be implemented outside of this crate.
+pub trait PietDrawingBackend: DrawingBackend<ErrorType = Error> + sealed::Sealed {
+    /// Run `draw` with the [`PietBackend`] that this backend draws through.
+    fn with_piet_backend<T>(&mut self, draw: impl FnOnce(&mut PietBackend<'_, '_>) -> T) -> T;
+}
+
+impl<'a, 'b> PietDrawingBackend for PietBackend<'a, 'b> {
+    fn with_piet_backend<T>(&mut self, draw: impl FnOnce(&mut PietBackend<'_, '_>) -> T) -> T {
+        draw(self)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<'a, 'b> Sealed for crate::PietBackend<'a, 'b> {}
+    impl Sealed for crate::PietBitmapBackend {}
+    impl Sealed for &mut crate::PietBitmapBackend {}
 }
 
 impl<'a, 'b> std::fmt::Debug for PietBackend<'a, 'b> {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         fmt.debug_struct("PietBackend")
             .field("size", &self.size)
+            .field("scale", &self.scale())
             .field("render_ctx", &"(not printable)")
             .finish()
     }
@@ -37,7 +391,12 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
     type ErrorType = Error;
 
     fn get_size(&self) -> (u32, u32) {
-        self.size
+        let (width, height) = self.size;
+        let scale = self.scale();
+        (
+            (width as f64 * scale).round() as u32,
+            (height as f64 * scale).round() as u32,
+        )
     }
 
     fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
@@ -45,9 +404,7 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
     }
 
     fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
-        self.render_ctx
-            .finish()
-            .map_err(|_| DrawingErrorKind::DrawingError(Error {}))
+        dispatch!(&mut self.target, backend => backend.present())
     }
 
     fn draw_pixel(
@@ -55,13 +412,7 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
         point: BackendCoord,
         color: BackendColor,
     ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
-        let x = point.0 as f64;
-        let y = point.1 as f64;
-        self.render_ctx.fill(
-            kurbo::Rect::new(x, y, x + 1., y + 1.),
-            &plotters_color_to_piet(&color),
-        );
-        Ok(())
+        dispatch!(&mut self.target, backend => backend.draw_pixel(point, color))
     }
 
     fn draw_line<S: plotters_backend::BackendStyle>(
@@ -70,25 +421,347 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
         to: BackendCoord,
         style: &S,
     ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
-        let from = plotters_point_to_kurbo_mid(from);
-        let to = plotters_point_to_kurbo_mid(to);
+        dispatch!(&mut self.target, backend => backend.draw_line(from, to, style))
+    }
 
-        self.render_ctx.stroke_styled(
-            kurbo::Line::new(from, to),
-            &plotters_color_to_piet(&style.color()),
-            style.stroke_width() as f64,
-            &STROKE_STYLE_SQUARE_CAP,
-        );
+    fn draw_rect<S: plotters_backend::BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.target, backend => {
+            backend.draw_rect(upper_left, bottom_right, style, fill)
+        })
+    }
+
+    fn draw_path<S: plotters_backend::BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.target, backend => backend.draw_path(path, style))
+    }
+
+    fn draw_circle<S: plotters_backend::BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.target, backend => backend.draw_circle(center, radius, style, fill))
+    }
+
+    fn fill_polygon<S: plotters_backend::BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.target, backend => backend.fill_polygon(vert, style))
+    }
+
+    fn draw_text<TStyle: plotters_backend::BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.target, backend => backend.draw_text(text, style, pos))
+    }
+
+    /// Measure the text with the same piet text layout that [`draw_text`](Self::draw_text)
+    /// would draw.
+    ///
+    /// The width is the advance width reported by piet, the height is the height of the
+    /// text box that is used for anchoring the text in `draw_text`.
+    fn estimate_text_size<TStyle: plotters_backend::BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&self.target, backend => backend.estimate_text_size(text, style))
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        size: (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.target, backend => backend.blit_bitmap(pos, size, src))
+    }
+}
+
+/// The drawing operations of [`PietBackend`] for a specific type of render context.
+struct RenderBackend<'a, R: RenderContext> {
+    render_ctx: &'a mut R,
+    /// Our own handle to the text API, `estimate_text_size` only gets `&self` so we
+    /// couldn't go through `render_ctx.text()` there.
+    text: R::Text,
+    scale: f64,
+    /// Pixels from `draw_pixel` that haven't been drawn yet, see `flush_pixels`.
+    pixels: Vec<(BackendCoord, BackendColor)>,
+    /// Lines and paths that haven't been stroked yet, see `flush_strokes`.
+    strokes: Option<PendingStrokes>,
+    /// Used for lines, paths and outlines.
+    stroke_style: StrokeStyle,
+    /// How many paths `flush_strokes` has stroked, to test that strokes are combined.
+    #[cfg(test)]
+    stroke_calls: usize,
+}
+
+/// Consecutive lines and paths with the same style, collected into one path.
+struct PendingStrokes {
+    color: Color,
+    width: u32,
+    style: StrokeStyle,
+    path: kurbo::BezPath,
+    /// Contains every pixel that the path touches, see `stroke_bounds`.
+    bounds: kurbo::Rect,
+}
+
+impl<'a, R: RenderContext> RenderBackend<'a, R> {
+    fn new(render_ctx: &'a mut R, scale: f64) -> RenderBackend<'a, R> {
+        let text = render_ctx.text().clone();
+        RenderBackend {
+            render_ctx,
+            text,
+            scale,
+            pixels: Vec::new(),
+            strokes: None,
+            stroke_style: DEFAULT_STROKE_STYLE,
+            #[cfg(test)]
+            stroke_calls: 0,
+        }
+    }
+
+    /// Run some drawing operations on the render context, with the plotters pixel
+    /// coordinates scaled to the units of the render context.
+    fn draw_scaled(&mut self, draw: impl FnOnce(&mut R)) -> Result<(), DrawingErrorKind<Error>> {
+        if self.scale == 1. {
+            draw(&mut *self.render_ctx);
+            return Ok(());
+        }
+
+        self.render_ctx.save().map_err(render_error)?;
+        self.render_ctx
+            .transform(kurbo::Affine::scale(1. / self.scale));
+        draw(&mut *self.render_ctx);
+        self.render_ctx.restore().map_err(render_error)
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
+        self.render_ctx.finish().map_err(render_error)
+    }
+
+    /// Draw everything that has been collected so far.
+    fn flush(&mut self) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush_pixels()?;
+        self.flush_strokes()
+    }
+
+    /// Draw the pixels collected by `draw_pixel` as a single image.
+    ///
+    /// This has to happen before anything else is drawn, so that the order of the
+    /// drawing operations stays the same.
+    fn flush_pixels(&mut self) -> Result<(), DrawingErrorKind<Error>> {
+        if self.pixels.is_empty() {
+            return Ok(());
+        }
+        let pixels = std::mem::take(&mut self.pixels);
+
+        let (mut min, mut max) = (pixels[0].0, pixels[0].0);
+        for &((x, y), _) in &pixels {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let width = (max.0 as i64 - min.0 as i64 + 1) as u64;
+        let height = (max.1 as i64 - min.1 as i64 + 1) as u64;
+
+        // A few pixels far apart would give a huge, mostly empty image.
+        if width.saturating_mul(height) > MAX_PIXEL_IMAGE_AREA_PER_PIXEL * pixels.len() as u64 {
+            return self.draw_scaled(|render_ctx| {
+                for ((x, y), color) in &pixels {
+                    let (x, y) = (*x as f64, *y as f64);
+                    let rect = kurbo::Rect::new(x, y, x + 1., y + 1.);
+                    render_ctx.fill(rect, &plotters_color_to_piet(color));
+                }
+            });
+        }
+
+        let (width, height) = (width as usize, height as usize);
+        let mut buf = vec![0; width * height * 4];
+        for ((x, y), color) in &pixels {
+            let idx = (y - min.1) as usize * width + (x - min.0) as usize;
+            blend_pixel_over(&mut buf[idx * 4..idx * 4 + 4], color);
+        }
+
+        let image = self
+            .render_ctx
+            .make_image(width, height, &buf, ImageFormat::RgbaSeparate)
+            .map_err(render_error)?;
+        let upper_left = plotters_point_to_kurbo_corner(min);
+        let rect = kurbo::Rect::from_origin_size(upper_left, (width as f64, height as f64));
+        self.draw_scaled(|render_ctx| {
+            render_ctx.draw_image(&image, rect, InterpolationMode::NearestNeighbor)
+        })
+    }
+
+    /// Stroke the lines and paths collected by `stroke`.
+    fn flush_strokes(&mut self) -> Result<(), DrawingErrorKind<Error>> {
+        match self.strokes.take() {
+            Some(strokes) => {
+                #[cfg(test)]
+                {
+                    self.stroke_calls += 1;
+                }
+                self.draw_scaled(|render_ctx| {
+                    render_ctx.stroke_styled(
+                        &strokes.path,
+                        &strokes.color,
+                        strokes.width as f64,
+                        &strokes.style,
+                    )
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Stroke a line or path, together with the previous ones if they have the same style.
+    ///
+    /// Strokes are only combined if they don't touch the same pixels. Where strokes
+    /// overlap, the antialiasing and the blending of transparent colors differ between
+    /// one path and several ones. This way horizontal and vertical lines look exactly the
+    /// same, the antialiased edges of diagonal ones may be off by one step. Dashed
+    /// strokes aren't combined either, because not every piet backend restarts the dash
+    /// pattern for every subpath.
+    fn stroke<S: plotters_backend::BackendStyle>(
+        &mut self,
+        path: impl IntoIterator<Item = kurbo::PathEl>,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush_pixels()?;
+
+        let color = plotters_color_to_piet(&style.color());
+        let width = style.stroke_width();
+        let path = kurbo::BezPath::from_vec(path.into_iter().collect());
+        let bounds = stroke_bounds(&path, width, &self.stroke_style);
+
+        let combine = self.strokes.as_ref().is_some_and(|strokes| {
+            strokes.color == color
+                && strokes.width == width
+                && strokes.style == self.stroke_style
+                && strokes.style.dash_pattern.is_empty()
+                && strokes.bounds.intersect(bounds).area() == 0.
+        });
+        if !combine {
+            self.flush_strokes()?;
+        }
+
+        match &mut self.strokes {
+            Some(strokes) => {
+                strokes.path.extend(path);
+                strokes.bounds = strokes.bounds.union(bounds);
+            }
+            None => {
+                self.strokes = Some(PendingStrokes {
+                    color,
+                    width,
+                    style: self.stroke_style.clone(),
+                    path,
+                    bounds,
+                })
+            }
+        }
+        Ok(())
+    }
+
+    fn stroke_shape<S: plotters_backend::BackendStyle>(
+        &mut self,
+        shape: impl Shape,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        self.stroke(plotters_shape_to_kurbo(shape), style)
+    }
+
+    fn fill_shape<S: plotters_backend::BackendStyle>(
+        &mut self,
+        shape: impl Shape,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        let path = plotters_shape_to_kurbo(shape);
+        let color = plotters_color_to_piet(&style.color());
+        self.draw_scaled(|render_ctx| render_ctx.fill(path, &color))
+    }
+
+    /// Clip everything that is drawn until the matching `pop_clip` to `rect`.
+    fn push_clip(&mut self, rect: kurbo::Rect) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
+        self.render_ctx.save().map_err(render_error)?;
+        let rect = kurbo::Affine::scale(1. / self.scale).transform_rect_bbox(rect);
+        self.render_ctx.clip(rect);
+        Ok(())
+    }
+
+    fn pop_clip(&mut self) -> Result<(), DrawingErrorKind<Error>> {
+        // What has been collected inside of the clip has to be drawn with it.
+        let flushed = self.flush();
+        self.render_ctx.restore().map_err(render_error)?;
+        flushed
+    }
+
+    /// Pixels are only collected here, they are drawn all at once by `flush_pixels`.
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush_strokes()?;
+        if color.alpha > 0.0 {
+            self.pixels.push((point, color));
+        }
         Ok(())
     }
 
+    fn draw_line<S: plotters_backend::BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        let from = plotters_point_to_kurbo_mid(from);
+        let to = plotters_point_to_kurbo_mid(to);
+        self.stroke(
+            [kurbo::PathEl::MoveTo(from), kurbo::PathEl::LineTo(to)],
+            style,
+        )
+    }
+
     fn draw_rect<S: plotters_backend::BackendStyle>(
         &mut self,
         upper_left: BackendCoord,
         bottom_right: BackendCoord,
         style: &S,
         fill: bool,
-    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
         let color = plotters_color_to_piet(&style.color());
 
         if fill {
@@ -98,37 +771,29 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
             bottom_right.y += 1.;
             let rect = kurbo::Rect::new(upper_left.x, upper_left.y, bottom_right.x, bottom_right.y);
 
-            self.render_ctx.fill(rect, &color);
+            self.draw_scaled(|render_ctx| render_ctx.fill(rect, &color))
         } else {
             let upper_left = plotters_point_to_kurbo_mid(upper_left);
             let bottom_right = plotters_point_to_kurbo_mid(bottom_right);
             let rect = kurbo::Rect::new(upper_left.x, upper_left.y, bottom_right.x, bottom_right.y);
 
-            self.render_ctx
-                .stroke(rect, &color, style.stroke_width() as f64);
+            let stroke_style = self.stroke_style.clone();
+            self.draw_scaled(|render_ctx| {
+                render_ctx.stroke_styled(rect, &color, style.stroke_width() as f64, &stroke_style)
+            })
         }
-
-        Ok(())
     }
 
     fn draw_path<S: plotters_backend::BackendStyle, I: IntoIterator<Item = BackendCoord>>(
         &mut self,
         path: I,
         style: &S,
-    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+    ) -> Result<(), DrawingErrorKind<Error>> {
         if style.color().alpha == 0.0 {
             return Ok(());
         }
 
-        let path: Vec<kurbo::PathEl> = plotters_path_to_kurbo(path).collect();
-
-        self.render_ctx.stroke_styled(
-            &*path,
-            &plotters_color_to_piet(&style.color()),
-            style.stroke_width() as f64,
-            &STROKE_STYLE_SQUARE_CAP,
-        );
-        Ok(())
+        self.stroke(plotters_path_to_kurbo(path), style)
     }
 
     fn draw_circle<S: plotters_backend::BackendStyle>(
@@ -137,25 +802,28 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
         radius: u32,
         style: &S,
         fill: bool,
-    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
         let center = plotters_point_to_kurbo_mid(center);
         let color = plotters_color_to_piet(&style.color());
         let circle = kurbo::Circle::new(center, radius as f64);
 
         if fill {
-            self.render_ctx.fill(circle, &color);
+            self.draw_scaled(|render_ctx| render_ctx.fill(circle, &color))
         } else {
-            self.render_ctx
-                .stroke(circle, &color, style.stroke_width() as f64);
+            let stroke_style = self.stroke_style.clone();
+            self.draw_scaled(|render_ctx| {
+                render_ctx.stroke_styled(circle, &color, style.stroke_width() as f64, &stroke_style)
+            })
         }
-        Ok(())
     }
 
     fn fill_polygon<S: plotters_backend::BackendStyle, I: IntoIterator<Item = BackendCoord>>(
         &mut self,
         vert: I,
         style: &S,
-    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
         if style.color().alpha == 0.0 {
             return Ok(());
         }
@@ -163,62 +831,215 @@ impl<'a, 'b> DrawingBackend for PietBackend<'a, 'b> {
         let path: Vec<kurbo::PathEl> = plotters_path_to_kurbo(vert)
             .chain(std::iter::once(kurbo::PathEl::ClosePath))
             .collect();
-        self.render_ctx
-            .fill(&*path, &plotters_color_to_piet(&style.color()));
-        Ok(())
+        self.draw_scaled(|render_ctx| {
+            render_ctx.fill(&*path, &plotters_color_to_piet(&style.color()))
+        })
+    }
+
+    fn draw_text<TStyle: plotters_backend::BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        let layout = plotters_text_to_piet_layout(&mut self.text, text, style)?;
+        let origin = text_layout_origin(&layout, style);
+
+        let pos = plotters_point_to_kurbo_corner(pos);
+        let rotation = match style.transform() {
+            FontTransform::None => 0.,
+            FontTransform::Rotate90 => std::f64::consts::FRAC_PI_2,
+            FontTransform::Rotate180 => std::f64::consts::PI,
+            FontTransform::Rotate270 => -std::f64::consts::FRAC_PI_2,
+        };
+
+        // Plotters rotates the text around the anchor point, so we move the origin
+        // there and position the layout relative to it.
+        let transform = kurbo::Affine::scale(1. / self.scale)
+            * kurbo::Affine::translate(pos.to_vec2())
+            * kurbo::Affine::rotate(rotation);
+        self.render_ctx.save().map_err(render_error)?;
+        self.render_ctx.transform(transform);
+        self.render_ctx.draw_text(&layout, origin);
+        self.render_ctx.restore().map_err(render_error)
+    }
+
+    fn estimate_text_size<TStyle: plotters_backend::BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Error>> {
+        let layout = plotters_text_to_piet_layout(&mut self.text.clone(), text, style)?;
+        let em = style.size() / PLOTTERS_FONT_SIZE_PER_EM;
+
+        Ok((layout.size().width.ceil() as u32, em.ceil() as u32))
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        (iw, ih): (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        self.flush()?;
+        if iw == 0 || ih == 0 {
+            return Ok(());
+        }
+
+        // Plotters always hands us RGB buffers here, see the default implementation
+        // of `blit_bitmap` in plotters_backend.
+        let format = ImageFormat::Rgb;
+        if src.len() < iw as usize * ih as usize * format.bytes_per_pixel() {
+            return Err(DrawingErrorKind::DrawingError(Error::InvalidImage(
+                piet_common::Error::InvalidInput.into(),
+            )));
+        }
+
+        let image = self
+            .render_ctx
+            .make_image(iw as usize, ih as usize, src, format)
+            .map_err(|e| DrawingErrorKind::DrawingError(Error::InvalidImage(e.into())))?;
+
+        let upper_left = plotters_point_to_kurbo_corner(pos);
+        let rect = kurbo::Rect::from_origin_size(upper_left, (iw as f64, ih as f64));
+        self.draw_scaled(|render_ctx| {
+            render_ctx.draw_image(&image, rect, InterpolationMode::NearestNeighbor)
+        })
     }
+}
+
+impl<'a, R: RenderContext> Drop for RenderBackend<'a, R> {
+    /// Draw the remaining pixels and strokes, in case `present` isn't called. This is the case in
+    /// druid, where the paint context is finished by druid itself.
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// A rectangle that contains all the pixels that stroking the path touches.
+fn stroke_bounds(path: &kurbo::BezPath, width: u32, style: &StrokeStyle) -> kurbo::Rect {
+    let half_width = width as f64 / 2.;
+    // Square caps stick out diagonally, miter joins up to the miter limit.
+    let extent = match style.line_join {
+        LineJoin::Miter { limit } if path.elements().len() > 2 => {
+            half_width * limit.max(std::f64::consts::SQRT_2)
+        }
+        _ => half_width * std::f64::consts::SQRT_2,
+    };
+    // One more pixel for the antialiasing.
+    path.bounding_box().inflate(extent + 1., extent + 1.)
+}
+
+/// If the bounding box of the collected pixels is larger than this times the number of
+/// pixels, they are drawn one by one instead of as an image.
+const MAX_PIXEL_IMAGE_AREA_PER_PIXEL: u64 = 64;
+
+/// Draw a pixel over the one in `dst`, both with straight alpha.
+fn blend_pixel_over(dst: &mut [u8], color: &BackendColor) {
+    // Round the alpha like `plotters_color_to_piet`, so that the result is the same as
+    // with filling a rectangle for the pixel.
+    let src_alpha = plotters_color_to_piet(color).as_rgba8().3 as f64 / 255.;
+    let dst_alpha = dst[3] as f64 / 255.;
+    let alpha = src_alpha + dst_alpha * (1. - src_alpha);
+    if alpha == 0. {
+        return;
+    }
+
+    let (r, g, b) = color.rgb;
+    for (dst, src) in dst.iter_mut().zip([r, g, b]) {
+        let value = src as f64 * src_alpha + *dst as f64 * dst_alpha * (1. - src_alpha);
+        *dst = (value / alpha).round() as u8;
+    }
+    dst[3] = (alpha * 255.).round() as u8;
+}
+
+/// Plotters interprets the font size as the height of the box that a line of text
+/// occupies, the actual em size of the font is smaller by a factor of 1.24. This
+/// is the same convention that the plotters font rasterizer and SVG backend use.
+const PLOTTERS_FONT_SIZE_PER_EM: f64 = 1.24;
+
+/// The plotters font size for a font with the given em size.
+///
+/// Use this to draw text as large as text of the same font size in piet or druid.
+///
+/// ```rust
+/// # use plotters::prelude::*;
+/// // The same size as a druid label with a 15 point font.
+/// let font = ("sans-serif", plotters_piet::font_size_for_em(15.)).into_font();
+/// ```
+pub fn font_size_for_em(em: f64) -> f64 {
+    em * PLOTTERS_FONT_SIZE_PER_EM
+}
+
+/// Where the baseline lies within the text box, measured from the top in em units.
+const PLOTTERS_BASELINE_EM: f64 = 0.76;
+
+fn plotters_text_to_piet_layout<T: Text, S: plotters_backend::BackendTextStyle>(
+    text_api: &mut T,
+    text: &str,
+    style: &S,
+) -> Result<T::TextLayout, DrawingErrorKind<Error>> {
+    let font_family = match style.family() {
+        plotters_backend::FontFamily::Serif => FontFamily::SERIF,
+        plotters_backend::FontFamily::SansSerif => FontFamily::SANS_SERIF,
+        plotters_backend::FontFamily::Monospace => FontFamily::MONOSPACE,
+        plotters_backend::FontFamily::Name(name) => text_api
+            .font_family(name)
+            .ok_or_else(|| DrawingErrorKind::DrawingError(Error::MissingFont(name.to_string())))?,
+    };
 
-    // For now we use the default text drawing provided by plotters. This is definitely slower,
-    // but at least we don't have to worry about matching the font size and offset which turns
-    // out to be trickier than expected.
-    // fn draw_text<TStyle: plotters_backend::BackendTextStyle>(
-    //     &mut self,
-    //     text: &str,
-    //     style: &TStyle,
-    //     pos: BackendCoord,
-    // ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
-    //     let pos = plotters_point_to_kurbo(pos);
-    //     let color = plotters_color_to_piet(&style.color());
-
-    //     let text_api = self.render_ctx.text();
-    //     let font_family = match style.family() {
-    //         plotters_backend::FontFamily::Serif => Ok(FontFamily::SERIF),
-    //         plotters_backend::FontFamily::SansSerif => Ok(FontFamily::SANS_SERIF),
-    //         plotters_backend::FontFamily::Monospace => Ok(FontFamily::MONOSPACE),
-    //         plotters_backend::FontFamily::Name(name) => text_api
-    //             .font_family(name)
-    //             .ok_or(piet_common::Error::MissingFont),
-    //     };
-
-    //     let (font_style, weight) = match style.style() {
-    //         plotters_backend::FontStyle::Normal => (FontStyle::Regular, FontWeight::REGULAR),
-    //         plotters_backend::FontStyle::Oblique => (FontStyle::Italic, FontWeight::REGULAR),
-    //         plotters_backend::FontStyle::Italic => (FontStyle::Italic, FontWeight::REGULAR),
-    //         plotters_backend::FontStyle::Bold => (FontStyle::Regular, FontWeight::BOLD),
-    //     };
-
-    //     let alignment = match style.anchor().h_pos {
-    //         plotters_backend::text_anchor::HPos::Left => TextAlignment::Start,
-    //         plotters_backend::text_anchor::HPos::Right => TextAlignment::End,
-    //         plotters_backend::text_anchor::HPos::Center => TextAlignment::Center,
-    //     };
-
-    //     let layout = text_api
-    //         .new_text_layout(String::from(text))
-    //         .font(font_family.unwrap(), style.size())
-    //         .text_color(color)
-    //         .alignment(alignment)
-    //         .default_attribute(TextAttribute::Style(font_style))
-    //         .default_attribute(TextAttribute::Weight(weight))
-    //         .build()
-    //         .unwrap();
-
-    //     // todo: style.anchor().v_pos
-    //     // todo: style.transform()
-
-    //     self.render_ctx.draw_text(&layout, pos);
-    //     Ok(())
-    // }
+    let (font_style, weight) = match style.style() {
+        plotters_backend::FontStyle::Normal => (FontStyle::Regular, FontWeight::REGULAR),
+        plotters_backend::FontStyle::Oblique => (FontStyle::Italic, FontWeight::REGULAR),
+        plotters_backend::FontStyle::Italic => (FontStyle::Italic, FontWeight::REGULAR),
+        plotters_backend::FontStyle::Bold => (FontStyle::Regular, FontWeight::BOLD),
+    };
+
+    text_api
+        .new_text_layout(String::from(text))
+        .font(font_family, style.size() / PLOTTERS_FONT_SIZE_PER_EM)
+        .text_color(plotters_color_to_piet(&style.color()))
+        .default_attribute(TextAttribute::Style(font_style))
+        .default_attribute(TextAttribute::Weight(weight))
+        .build()
+        .map_err(|e| DrawingErrorKind::DrawingError(Error::TextLayout(e.into())))
+}
+
+/// Compute the position at which the layout has to be drawn such that the anchor
+/// of the text lands on the origin.
+///
+/// The text box is positioned the same way as in the plotters font rasterizer, so
+/// that switching between the two doesn't move the labels around.
+fn text_layout_origin<L: TextLayout, S: plotters_backend::BackendTextStyle>(
+    layout: &L,
+    style: &S,
+) -> kurbo::Point {
+    let em = style.size() / PLOTTERS_FONT_SIZE_PER_EM;
+    let width = layout.size().width;
+    let baseline = layout
+        .line_metric(0)
+        .map_or(PLOTTERS_BASELINE_EM * em, |metric| metric.baseline);
+
+    let x = match style.anchor().h_pos {
+        HPos::Left => 0.,
+        HPos::Right => -width,
+        HPos::Center => -width / 2.,
+    };
+    let box_top = match style.anchor().v_pos {
+        VPos::Top => 0.,
+        VPos::Center => -em / 2.,
+        VPos::Bottom => -em,
+    };
+
+    kurbo::Point {
+        x,
+        y: box_top + PLOTTERS_BASELINE_EM * em - baseline,
+    }
 }
 
 fn plotters_color_to_piet(col: &BackendColor) -> piet_common::Color {
@@ -232,6 +1053,15 @@ fn plotters_point_to_kurbo_mid((x, y): BackendCoord) -> kurbo::Point {
     }
 }
 
+/// How far the curves of shapes may be off when they are turned into paths, in pixels.
+const SHAPE_TOLERANCE: f64 = 0.1;
+
+/// Move a shape in plotters coordinates with fractional parts onto the pixel centers, like
+/// `plotters_point_to_kurbo_mid`.
+fn plotters_shape_to_kurbo(shape: impl Shape) -> kurbo::BezPath {
+    kurbo::Affine::translate((0.5, 0.5)) * shape.into_path(SHAPE_TOLERANCE)
+}
+
 fn plotters_point_to_kurbo_corner((x, y): BackendCoord) -> kurbo::Point {
     kurbo::Point {
         x: x as f64,
@@ -284,13 +1114,19 @@ fn plotters_path_to_kurbo(
     PlottersPathToKurbo::new(path.into_iter())
 }
 
-const STROKE_STYLE_SQUARE_CAP: StrokeStyle = StrokeStyle::new().line_cap(LineCap::Square);
+/// The stroke style that is used unless another one is set with
+/// [`PietBackend::set_stroke_style`]. The square caps make lines end exactly on the
+/// pixels given by plotters.
+pub const DEFAULT_STROKE_STYLE: StrokeStyle = StrokeStyle::new().line_cap(LineCap::Square);
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use piet_common::RenderContext;
     use plotters::prelude::*;
+    use plotters::style::text_anchor::{HPos, Pos, VPos};
+    // The piet `Color` from `super` shadows the plotters trait of the same name.
+    use plotters::style::Color as _;
 
     #[test]
     fn fill_root_white() {
@@ -303,14 +1139,12 @@ mod tests {
         {
             let mut render_ctx = bitmap.render_context();
 
-            let piet_backend = PietBackend {
-                size: (width as u32, height as u32),
-                render_ctx: &mut render_ctx,
-            };
+            let piet_backend = PietBackend::new((width as u32, height as u32), &mut render_ctx);
 
             let root = piet_backend.into_drawing_area();
             root.fill(&WHITE).unwrap();
 
+            drop(root);
             render_ctx.finish().unwrap();
         }
 
@@ -322,6 +1156,380 @@ mod tests {
         assert_eq!(buf, [255; 6 * 4]);
     }
 
+    #[test]
+    fn draw_text_anchored_bottom_right() {
+        let width = 60;
+        let height = 60;
+
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(width, height, 1.0).unwrap();
+
+        {
+            let mut render_ctx = bitmap.render_context();
+
+            let piet_backend = PietBackend::new((width as u32, height as u32), &mut render_ctx);
+
+            let root = piet_backend.into_drawing_area();
+            let style = TextStyle::from(("sans-serif", 20).into_font())
+                .pos(Pos::new(HPos::Right, VPos::Bottom));
+            root.draw_text("X", &style, (30, 30)).unwrap();
+
+            drop(root);
+            render_ctx.finish().unwrap();
+        }
+
+        let mut buf = vec![0; width * height * 4];
+        bitmap
+            .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+            .unwrap();
+
+        let mut any_ink = false;
+        for (i, pixel) in buf.chunks(4).enumerate() {
+            if pixel[3] > 0 {
+                any_ink = true;
+                let (x, y) = (i % width, i / width);
+                // allow a pixel of antialiasing around the anchor
+                assert!(x <= 31 && y <= 31, "ink at ({x}, {y}) past the anchor");
+            }
+        }
+        assert!(any_ink);
+    }
+
+    #[test]
+    fn estimate_text_size_matches_font_size() {
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(1, 1, 1.0).unwrap();
+        let mut render_ctx = bitmap.render_context();
+        let piet_backend = PietBackend::new((1, 1), &mut render_ctx);
+
+        let style = TextStyle::from(("sans-serif", 31).into_font());
+        let (short_width, height) = piet_backend.estimate_text_size("1", &style).unwrap();
+        let (long_width, _) = piet_backend.estimate_text_size("1000", &style).unwrap();
+
+        assert_eq!(height, 25);
+        assert!(short_width > 0);
+        assert!(long_width > 3 * short_width);
+
+        drop(piet_backend);
+        render_ctx.finish().unwrap();
+    }
+
+    #[test]
+    fn blit_bitmap_in_place() {
+        let width = 3;
+        let height = 2;
+
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(width, height, 1.0).unwrap();
+
+        {
+            let mut render_ctx = bitmap.render_context();
+            let mut piet_backend = PietBackend::new((width as u32, height as u32), &mut render_ctx);
+
+            let image = [255, 0, 0, 0, 0, 255];
+            piet_backend.blit_bitmap((1, 1), (2, 1), &image).unwrap();
+
+            drop(piet_backend);
+            render_ctx.finish().unwrap();
+        }
+
+        let mut buf = [0; 6 * 4];
+        bitmap
+            .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+            .unwrap();
+
+        #[rustfmt::skip]
+        let expected = [
+            0, 0, 0, 0,   0, 0, 0, 0,     0, 0, 0, 0,
+            0, 0, 0, 0,   255, 0, 0, 255, 0, 0, 255, 255,
+        ];
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn blit_bitmap_too_short() {
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(3, 2, 1.0).unwrap();
+        let mut render_ctx = bitmap.render_context();
+        let mut piet_backend = PietBackend::new((3, 2), &mut render_ctx);
+
+        let result = piet_backend.blit_bitmap((0, 0), (2, 1), &[0; 5]);
+        match result {
+            Err(DrawingErrorKind::DrawingError(e @ Error::InvalidImage(_))) => {
+                assert!(std::error::Error::source(&e).is_some());
+            }
+            _ => panic!("expected an InvalidImage error"),
+        }
+
+        drop(piet_backend);
+        render_ctx.finish().unwrap();
+    }
+
+    #[test]
+    fn piet_error_keeps_kind() {
+        let error = PietError::from(piet_common::Error::MissingFeature("png"));
+        assert_eq!(error.kind(), &PietErrorKind::MissingFeature("png"));
+
+        let io_error = std::io::Error::other("device lost");
+        let error = PietError::from(piet_common::Error::BackendError(Box::new(io_error)));
+        assert_eq!(
+            error.kind(),
+            &PietErrorKind::BackendError("device lost".to_owned())
+        );
+        assert_eq!(error.to_string(), "backend error: device lost");
+    }
+
+    #[test]
+    fn scaled_backend_draws_device_pixels() {
+        let mut device = piet_common::Device::new().unwrap();
+        // The bitmap has twice the resolution of the render context units.
+        let mut bitmap = device.bitmap_target(4, 4, 2.0).unwrap();
+
+        {
+            let mut render_ctx = bitmap.render_context();
+            let piet_backend = PietBackend::with_scale((2, 2), 2., &mut render_ctx);
+            assert_eq!(piet_backend.get_size(), (4, 4));
+
+            let root = piet_backend.into_drawing_area();
+            root.draw_pixel((1, 1), &WHITE).unwrap();
+
+            drop(root);
+            render_ctx.finish().unwrap();
+        }
+
+        let mut buf = [0; 16 * 4];
+        bitmap
+            .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+            .unwrap();
+
+        for (i, pixel) in buf.chunks(4).enumerate() {
+            let expected = if i == 4 + 1 { 255 } else { 0 };
+            assert_eq!(pixel, [expected; 4], "pixel {i}");
+        }
+    }
+
+    #[test]
+    fn batched_pixels_match_single_fills() {
+        let pixels = [
+            ((0, 0), RED.to_backend_color()),
+            ((1, 0), BLUE.mix(0.5).to_backend_color()),
+            ((1, 0), GREEN.mix(0.5).to_backend_color()),
+            ((3, 2), BLACK.mix(0.25).to_backend_color()),
+        ];
+
+        let render = |batched: bool| {
+            let mut device = piet_common::Device::new().unwrap();
+            let mut bitmap = device.bitmap_target(4, 3, 1.0).unwrap();
+            {
+                let mut render_ctx = bitmap.render_context();
+                if batched {
+                    let mut piet_backend = PietBackend::new((4, 3), &mut render_ctx);
+                    for (point, color) in pixels {
+                        piet_backend.draw_pixel(point, color).unwrap();
+                    }
+                    drop(piet_backend);
+                } else {
+                    for ((x, y), color) in pixels {
+                        let (x, y) = (x as f64, y as f64);
+                        let rect = kurbo::Rect::new(x, y, x + 1., y + 1.);
+                        render_ctx.fill(rect, &plotters_color_to_piet(&color));
+                    }
+                }
+                render_ctx.finish().unwrap();
+            }
+
+            let mut buf = vec![0; 12 * 4];
+            bitmap
+                .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+                .unwrap();
+            buf
+        };
+
+        let batched = render(true);
+        let single = render(false);
+        for (b, s) in batched.iter().zip(&single) {
+            assert!(b.abs_diff(*s) <= 1, "{batched:?} != {single:?}");
+        }
+    }
+
+    #[test]
+    fn pixels_are_drawn_before_other_primitives() {
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(2, 1, 1.0).unwrap();
+
+        {
+            let mut render_ctx = bitmap.render_context();
+            let mut piet_backend = PietBackend::new((2, 1), &mut render_ctx);
+
+            piet_backend
+                .draw_pixel((0, 0), RED.to_backend_color())
+                .unwrap();
+            piet_backend.draw_rect((0, 0), (1, 0), &BLUE, true).unwrap();
+            piet_backend
+                .draw_pixel((1, 0), WHITE.to_backend_color())
+                .unwrap();
+            piet_backend.present().unwrap();
+        }
+
+        let mut buf = [0; 2 * 4];
+        bitmap
+            .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+            .unwrap();
+        assert_eq!(buf, [0, 0, 255, 255, 255, 255, 255, 255]);
+    }
+
+    /// Draw `lines` through a `PietBackend`, or straight on the render context if
+    /// `batched` is false. Returns the pixels and how many paths the backend stroked.
+    fn render_lines(
+        lines: &[(Vec<BackendCoord>, plotters::style::RGBAColor, u32)],
+        batched: bool,
+    ) -> (Vec<u8>, usize) {
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(32, 32, 1.0).unwrap();
+        let mut stroke_calls = 0;
+        {
+            let mut render_ctx = bitmap.render_context();
+            if batched {
+                let mut piet_backend = PietBackend::new((32, 32), &mut render_ctx);
+                for (path, color, width) in lines {
+                    let style = color.stroke_width(*width);
+                    if path.len() == 2 {
+                        piet_backend.draw_line(path[0], path[1], &style).unwrap();
+                    } else {
+                        piet_backend.draw_path(path.clone(), &style).unwrap();
+                    }
+                }
+                piet_backend.present().unwrap();
+                stroke_calls = dispatch!(&piet_backend.target, backend => backend.stroke_calls);
+                drop(piet_backend);
+            } else {
+                for (path, color, width) in lines {
+                    let path: Vec<_> = plotters_path_to_kurbo(path.clone()).collect();
+                    render_ctx.stroke_styled(
+                        &*path,
+                        &plotters_color_to_piet(&color.to_backend_color()),
+                        *width as f64,
+                        &DEFAULT_STROKE_STYLE,
+                    );
+                }
+            }
+            render_ctx.finish().unwrap();
+        }
+
+        let mut buf = vec![0; 32 * 32 * 4];
+        bitmap
+            .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+            .unwrap();
+        (buf, stroke_calls)
+    }
+
+    #[test]
+    fn batched_strokes_match_separate_strokes() {
+        // A mesh the way `draw_mesh` draws it, all vertical lines and then all horizontal
+        // ones. Each direction becomes one path.
+        let mut mesh = Vec::new();
+        for x in (2..30).step_by(4) {
+            mesh.push((vec![(x, 0), (x, 31)], BLACK.mix(0.5), 1));
+        }
+        for y in (2..30).step_by(4) {
+            mesh.push((vec![(0, y), (31, y)], BLACK.mix(0.5), 1));
+        }
+
+        // Diagonal segments with the same style that don't touch each other. The
+        // antialiasing of their edges may be off by one step in one path.
+        let segments: Vec<_> = (0..5)
+            .map(|i| {
+                (
+                    vec![(1 + i * 6, 8 + i), (3 + i * 6, 20 + i)],
+                    RED.mix(1.),
+                    1,
+                )
+            })
+            .collect();
+
+        // Lines that cross the previous one, a series with semi-transparent lines and a
+        // thick path. None of them can be combined.
+        let mut crossing = Vec::new();
+        for i in (2..30).step_by(4) {
+            crossing.push((vec![(i, 0), (i, 31)], BLACK.mix(1.), 1));
+            crossing.push((vec![(0, i), (31, i)], BLACK.mix(1.), 1));
+        }
+        crossing.push((vec![(0, 31), (15, 3), (31, 20)], RED.mix(0.5), 1));
+        crossing.push((vec![(0, 0), (31, 31)], RED.mix(0.5), 1));
+        crossing.push((vec![(3, 28), (10, 5), (20, 25), (28, 3)], BLUE.mix(1.), 3));
+        crossing.push((vec![(5, 30), (30, 5)], BLUE.mix(1.), 3));
+        let crossing_calls = crossing.len();
+
+        for (lines, expected_calls, tolerance) in [
+            (mesh, 2, 0),
+            (segments, 1, 1),
+            (crossing, crossing_calls, 0),
+        ] {
+            let (batched, stroke_calls) = render_lines(&lines, true);
+            let (separate, _) = render_lines(&lines, false);
+            assert!(batched
+                .iter()
+                .zip(&separate)
+                .all(|(a, b)| a.abs_diff(*b) <= tolerance));
+            assert_eq!(stroke_calls, expected_calls);
+        }
+    }
+
+    #[test]
+    fn stroke_style_dash_pattern() {
+        let mut device = piet_common::Device::new().unwrap();
+        let mut bitmap = device.bitmap_target(10, 1, 1.0).unwrap();
+
+        {
+            let mut render_ctx = bitmap.render_context();
+            let mut piet_backend = PietBackend::new((10, 1), &mut render_ctx);
+            let dashed = StrokeStyle::new()
+                .line_cap(LineCap::Butt)
+                .dash_pattern(&[2., 2.]);
+            piet_backend.set_stroke_style(dashed.clone());
+            assert!(piet_backend.stroke_style() == &dashed);
+
+            piet_backend.draw_line((0, 0), (9, 0), &BLACK).unwrap();
+            piet_backend.present().unwrap();
+            drop(piet_backend);
+            render_ctx.finish().unwrap();
+        }
+
+        let mut buf = [0; 10 * 4];
+        bitmap
+            .copy_raw_pixels(piet_common::ImageFormat::RgbaPremul, &mut buf)
+            .unwrap();
+        let alpha = |x: usize| buf[x * 4 + 3];
+
+        // The line starts in the middle of the first pixel, the dashes cover the pixels
+        // 1, 5 and 9 and leave out 3 and 7.
+        assert_eq!(alpha(1), 255);
+        assert_eq!(alpha(3), 0);
+        assert_eq!(alpha(5), 255);
+        assert_eq!(alpha(7), 0);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn draw_svg() {
+        let mut render_ctx = piet_svg::RenderContext::new(kurbo::Size::new(40., 30.));
+
+        {
+            let piet_backend = PietBackend::new_svg((40, 30), &mut render_ctx);
+            assert_eq!(piet_backend.get_size(), (40, 30));
+
+            let root = piet_backend.into_drawing_area();
+            root.draw(&Circle::new((20, 15), 5, RED.filled())).unwrap();
+            root.present().unwrap();
+        }
+
+        let mut svg = Vec::new();
+        render_ctx.write(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("<path"));
+    }
+
     #[test]
     fn test_plotters_path_to_kurbo() {
         let path = vec![(1, 2), (3, 4), (5, 6)];
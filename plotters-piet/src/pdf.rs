@@ -0,0 +1,116 @@
+//! Multi-page PDF documents, drawn through piet-cairo.
+
+use std::path::Path;
+
+use piet_cairo::CairoRenderContext;
+use piet_common::RenderContext;
+
+use crate::{Error, PietBackend, PietError, PietErrorKind};
+
+/// A PDF file that plots can be drawn into, one page at a time.
+///
+/// Every page has the same size, given in PDF points (1/72 inch). Plotters sees one
+/// point as one pixel, so font sizes and line widths are given in points as well.
+///
+/// This is only available where piet draws with cairo, i.e. on Linux and the BSDs.
+///
+/// ```rust,no_run
+/// # use plotters::prelude::*;
+/// # use plotters_piet::PdfDocument;
+/// let mut pdf = PdfDocument::create("report.pdf", (595, 842)).unwrap();
+/// for title in ["first", "second"] {
+///     pdf.add_page(|backend| {
+///         let root = backend.into_drawing_area();
+///         root.fill(&WHITE)?;
+///         root.titled(title, ("sans-serif", 30))?;
+///         // draw a chart as usual
+///         Ok::<_, Box<dyn std::error::Error>>(())
+///     })
+///     .unwrap()
+///     .unwrap();
+/// }
+/// pdf.finish().unwrap();
+/// ```
+pub struct PdfDocument {
+    surface: cairo::PdfSurface,
+    context: cairo::Context,
+    size: (u32, u32),
+}
+
+impl PdfDocument {
+    /// Create a PDF file at `path` whose pages are `size` points large.
+    pub fn create(path: impl AsRef<Path>, size: (u32, u32)) -> Result<PdfDocument, Error> {
+        let surface =
+            cairo::PdfSurface::new(size.0 as f64, size.1 as f64, path).map_err(cairo_error)?;
+        let context = cairo::Context::new(&surface).map_err(cairo_error)?;
+        Ok(PdfDocument {
+            surface,
+            context,
+            size,
+        })
+    }
+
+    /// Draw a new page.
+    ///
+    /// `draw` gets a backend for the page and can return whatever it likes, e.g. the
+    /// result of the plotters calls. The page is finished once `draw` returns.
+    pub fn add_page<R>(&mut self, draw: impl FnOnce(PietBackend) -> R) -> Result<R, Error> {
+        let mut render_ctx = CairoRenderContext::new(&self.context);
+        let result = draw(PietBackend::new(self.size, &mut render_ctx));
+        render_ctx.finish().map_err(|e| Error::Render(e.into()))?;
+        drop(render_ctx);
+
+        self.context.show_page().map_err(cairo_error)?;
+        Ok(result)
+    }
+
+    /// Finish writing the file.
+    ///
+    /// The file is also finished when the document is dropped, but errors are lost then.
+    pub fn finish(self) -> Result<(), Error> {
+        self.surface.finish();
+        self.surface.status().map_err(cairo_error)
+    }
+}
+
+impl std::fmt::Debug for PdfDocument {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("PdfDocument")
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+fn cairo_error(e: cairo::Error) -> Error {
+    Error::Render(PietError {
+        kind: PietErrorKind::BackendError(e.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn write_two_pages() {
+        let path = std::env::temp_dir().join("plotters-piet-write-two-pages.pdf");
+
+        let mut pdf = PdfDocument::create(&path, (100, 80)).unwrap();
+        for color in [RED, BLUE] {
+            let size = pdf
+                .add_page(|backend| {
+                    let root = backend.into_drawing_area();
+                    root.fill(&color).unwrap();
+                    root.dim_in_pixel()
+                })
+                .unwrap();
+            assert_eq!(size, (100, 80));
+        }
+        pdf.finish().unwrap();
+
+        let content = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(content.starts_with(b"%PDF"));
+    }
+}
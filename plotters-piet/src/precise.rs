@@ -0,0 +1,317 @@
+//! Drawing with coordinates that aren't rounded to whole pixels.
+//!
+//! Plotters maps every coordinate to a whole pixel before it reaches the backend, so a
+//! slowly rising line turns into a staircase. The elements in here are drawn with
+//! [`DrawPrecise::draw_precise`] instead, which maps their coordinates to fractional
+//! pixels and lets piet antialias them.
+
+use piet_common::kurbo;
+use plotters::chart::ChartContext;
+use plotters::coord::{cartesian::Cartesian2d, ranged1d::Ranged, CoordTranslate};
+use plotters::drawing::{DrawingArea, DrawingAreaErrorKind};
+use plotters::element::{Drawable, PointCollection};
+use plotters::style::ShapeStyle;
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+
+use crate::{Error, PietBackend, PietDrawingBackend};
+
+/// Plotters coordinate specs only map to whole pixels, so we let them map to a range
+/// that is this many times larger and scale the result back down.
+///
+/// Larger values give a finer resolution, but values far outside the range of an axis
+/// overflow sooner.
+const SUBPIXEL_STEPS: i32 = 16;
+
+/// A coordinate system that can map to pixel coordinates with fractional parts.
+pub trait PreciseCoordTranslate: CoordTranslate {
+    /// Map a coordinate to the pixel that plotters would map it to, but without rounding.
+    ///
+    /// Like the plotters pixel coordinates, whole numbers are the centers of pixels.
+    fn translate_precise(&self, from: &Self::From) -> kurbo::Point;
+}
+
+impl<X: Ranged, Y: Ranged> PreciseCoordTranslate for Cartesian2d<X, Y> {
+    fn translate_precise(&self, (x, y): &Self::From) -> kurbo::Point {
+        // Cartesian2d doesn't tell us the pixel limits that it maps to, but they are where
+        // the ends of the value ranges end up.
+        let (x_range, y_range) = (self.get_x_range(), self.get_y_range());
+        let start = self.translate(&(x_range.start, y_range.start));
+        let end = self.translate(&(x_range.end, y_range.end));
+
+        kurbo::Point::new(
+            map_precise(self.x_spec(), x, (start.0, end.0)),
+            map_precise(self.y_spec(), y, (start.1, end.1)),
+        )
+    }
+}
+
+fn map_precise<R: Ranged>(spec: &R, value: &R::ValueType, (start, end): (i32, i32)) -> f64 {
+    let limits = (
+        start.saturating_mul(SUBPIXEL_STEPS),
+        end.saturating_mul(SUBPIXEL_STEPS),
+    );
+    spec.map(value, limits) as f64 / SUBPIXEL_STEPS as f64
+}
+
+/// An element that can be drawn with [`DrawPrecise::draw_precise`].
+///
+/// Implement this with [`PietBackend::stroke_shape`] and [`PietBackend::fill_shape`] for
+/// elements of your own.
+pub trait PreciseDrawable<Coord> {
+    /// Draw the element, `map` turns its coordinates into pixel coordinates.
+    fn draw_precise(
+        &self,
+        map: &dyn Fn(&Coord) -> kurbo::Point,
+        backend: &mut PietBackend,
+    ) -> Result<(), DrawingErrorKind<Error>>;
+}
+
+/// Draw [`PreciseDrawable`] elements on a drawing area or the plotting area of a chart.
+///
+/// The element is clipped to the area. Plotters doesn't know about it, so it can't be part
+/// of a series with a legend entry.
+///
+/// ```rust
+/// # use plotters::prelude::*;
+/// # use plotters_piet::PietBitmapBackend;
+/// use plotters_piet::precise::{DrawPrecise, PrecisePathElement};
+///
+/// let backend = PietBitmapBackend::new((640, 480)).unwrap();
+/// let root = backend.into_drawing_area();
+/// let chart = ChartBuilder::on(&root)
+///     .build_cartesian_2d(0.0..100.0, 0.0..1.0)
+///     .unwrap();
+///
+/// let points: Vec<_> = (0..=100).map(|x| (x as f64, (x as f64 / 100.).powi(2))).collect();
+/// chart
+///     .draw_precise(&PrecisePathElement::new(points, &RED))
+///     .unwrap();
+/// ```
+pub trait DrawPrecise<Coord> {
+    /// Draw `element` with its coordinates mapped to fractional pixels.
+    fn draw_precise<E: PreciseDrawable<Coord>>(
+        &self,
+        element: &E,
+    ) -> Result<(), DrawingAreaErrorKind<Error>>;
+}
+
+impl<DB: PietDrawingBackend, CT: PreciseCoordTranslate> DrawPrecise<CT::From>
+    for DrawingArea<DB, CT>
+{
+    fn draw_precise<E: PreciseDrawable<CT::From>>(
+        &self,
+        element: &E,
+    ) -> Result<(), DrawingAreaErrorKind<Error>> {
+        let coord = self.as_coord_spec();
+        let (x, y) = self.get_pixel_range();
+        self.draw(&Precise {
+            element,
+            map: &|from| coord.translate_precise(from),
+            clip: kurbo::Rect::new(x.start as f64, y.start as f64, x.end as f64, y.end as f64),
+        })
+    }
+}
+
+impl<'a, DB: PietDrawingBackend, CT: PreciseCoordTranslate> DrawPrecise<CT::From>
+    for ChartContext<'a, DB, CT>
+{
+    fn draw_precise<E: PreciseDrawable<CT::From>>(
+        &self,
+        element: &E,
+    ) -> Result<(), DrawingAreaErrorKind<Error>> {
+        self.plotting_area().draw_precise(element)
+    }
+}
+
+/// Passes a [`PreciseDrawable`] through `DrawingArea::draw`, which is the only way to get
+/// to the backend of a drawing area. It has no points for plotters to map.
+struct Precise<'e, Coord, E> {
+    element: &'e E,
+    map: &'e dyn Fn(&Coord) -> kurbo::Point,
+    /// The drawing area, with whole numbers on the corners of the pixels.
+    clip: kurbo::Rect,
+}
+
+impl<'a, 'e, Coord, E> PointCollection<'a, Coord> for &'a Precise<'e, Coord, E> {
+    type Point = &'a Coord;
+    type IntoIter = std::iter::Empty<&'a Coord>;
+
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::empty()
+    }
+}
+
+impl<'e, Coord, E, DB> Drawable<DB> for Precise<'e, Coord, E>
+where
+    E: PreciseDrawable<Coord>,
+    DB: PietDrawingBackend,
+{
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        _pos: I,
+        backend: &mut DB,
+        _parent_dim: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        backend.with_piet_backend(|backend| {
+            backend.clipped(self.clip, |backend| {
+                self.element.draw_precise(self.map, backend)
+            })
+        })
+    }
+}
+
+/// A line through a series of points, like [`plotters::element::PathElement`].
+#[derive(Clone)]
+pub struct PrecisePathElement<Coord> {
+    points: Vec<Coord>,
+    style: ShapeStyle,
+}
+
+impl<Coord> PrecisePathElement<Coord> {
+    /// Create a line through `points`.
+    pub fn new<P: Into<Vec<Coord>>, S: Into<ShapeStyle>>(points: P, style: S) -> Self {
+        PrecisePathElement {
+            points: points.into(),
+            style: style.into(),
+        }
+    }
+}
+
+impl<Coord> PreciseDrawable<Coord> for PrecisePathElement<Coord> {
+    fn draw_precise(
+        &self,
+        map: &dyn Fn(&Coord) -> kurbo::Point,
+        backend: &mut PietBackend,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        let mut path = kurbo::BezPath::new();
+        let mut points = self.points.iter().map(map);
+        if let Some(first) = points.next() {
+            path.move_to(first);
+            points.for_each(|point| path.line_to(point));
+        }
+        backend.stroke_shape(path, &self.style)
+    }
+}
+
+/// A circle with a radius in pixels, like [`plotters::element::Circle`].
+///
+/// This is filled if the style is, e.g. for the points of a scatter plot.
+#[derive(Clone)]
+pub struct PreciseCircle<Coord> {
+    center: Coord,
+    radius: f64,
+    style: ShapeStyle,
+}
+
+impl<Coord> PreciseCircle<Coord> {
+    /// Create a circle around `center` with a radius of `radius` pixels.
+    pub fn new<S: Into<ShapeStyle>>(center: Coord, radius: f64, style: S) -> Self {
+        PreciseCircle {
+            center,
+            radius,
+            style: style.into(),
+        }
+    }
+}
+
+impl<Coord> PreciseDrawable<Coord> for PreciseCircle<Coord> {
+    fn draw_precise(
+        &self,
+        map: &dyn Fn(&Coord) -> kurbo::Point,
+        backend: &mut PietBackend,
+    ) -> Result<(), DrawingErrorKind<Error>> {
+        let circle = kurbo::Circle::new(map(&self.center), self.radius);
+        if self.style.filled {
+            backend.fill_shape(circle, &self.style)
+        } else {
+            backend.stroke_shape(circle, &self.style)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PietBitmapBackend;
+    use plotters::coord::types::RangedCoordf64;
+    use plotters::prelude::*;
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn translate_precise_keeps_fractions() {
+        // Laid out like the plotting area of a chart, with the y axis pointing up.
+        let coord: Cartesian2d<RangedCoordf64, RangedCoordf64> =
+            Cartesian2d::new(0.0..10.0, 0.0..1.0, (0..100, 100..0));
+
+        assert_eq!(
+            coord.translate_precise(&(0., 0.)),
+            kurbo::Point::new(0., 100.)
+        );
+        assert_eq!(
+            coord.translate_precise(&(10., 1.)),
+            kurbo::Point::new(100., 0.)
+        );
+        assert_eq!(
+            coord.translate_precise(&(0.05, 0.25)),
+            kurbo::Point::new(0.5, 75.)
+        );
+
+        let point = coord.translate_precise(&(3.33, 0.333));
+        assert_eq!(coord.translate(&(3.33, 0.333)), (33, 66));
+        assert!((point.x - 33.3).abs() <= 1. / SUBPIXEL_STEPS as f64);
+        assert!((point.y - 66.7).abs() <= 1. / SUBPIXEL_STEPS as f64);
+    }
+
+    #[test]
+    fn translate_precise_matches_chart_pixels() {
+        let backend = PietBitmapBackend::new((120, 70)).unwrap();
+        let root = backend.into_drawing_area();
+        let chart = ChartBuilder::on(&root)
+            .margin(10)
+            .build_cartesian_2d(0.0..10.0, 0.0..5.0)
+            .unwrap();
+        let coord = chart.as_coord_spec();
+
+        for x in 0..=10 {
+            for y in 0..=5 {
+                let point = (x as f64, y as f64);
+                let (px, py) = coord.translate(&point);
+                assert_eq!(
+                    coord.translate_precise(&point),
+                    kurbo::Point::new(px as f64, py as f64)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn translate_precise_with_y_axis_down() {
+        let coord: Cartesian2d<RangedCoordf64, RangedCoordf64> =
+            Cartesian2d::new(0.0..1.0, 0.0..1.0, (10..20, 10..20));
+        assert_eq!(
+            coord.translate_precise(&(0.25, 0.75)),
+            kurbo::Point::new(12.5, 17.5)
+        );
+    }
+
+    #[test]
+    fn draw_precise_is_clipped() {
+        let mut backend = PietBitmapBackend::new((20, 10)).unwrap();
+        {
+            let root = (&mut backend).into_drawing_area();
+            let (left, _) = root.split_horizontally(10);
+            let left = left.apply_coord_spec(Cartesian2d::<RangedCoordf64, RangedCoordf64>::new(
+                0.0..1.0,
+                0.0..1.0,
+                (0..10, 0..10),
+            ));
+            left.draw_precise(&PreciseCircle::new((1., 0.5), 4., RED.filled()))
+                .unwrap();
+        }
+        let pixels = backend.into_rgba_buffer().unwrap();
+        let alpha = |x: usize, y: usize| pixels[(y * 20 + x) * 4 + 3];
+
+        assert_eq!(alpha(9, 5), 255);
+        assert_eq!(alpha(10, 5), 0);
+    }
+}
@@ -0,0 +1,334 @@
+//! Golden image tests for the drawing primitives of the piet backend.
+//!
+//! Every test draws on a `PietBitmapBackend` and compares the result with a reference
+//! PNG in `tests/golden`. A missing reference fails the test, running the tests with
+//! `PLOTTERS_PIET_BLESS=1` creates it and replaces all references that differ. Check the
+//! new images in after looking at them.
+//!
+//! When a test fails, the actual output and an image that marks the differing pixels in
+//! red are written to `target/tmp/golden`.
+
+use std::{fs::File, io::BufWriter, path::Path};
+
+use piet_common::kurbo::{Arc, CubicBez, RoundedRect};
+use piet_common::{LineCap, LineJoin, StrokeStyle};
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters_backend::{BackendColor, DrawingBackend};
+use plotters_piet::precise::{DrawPrecise, PreciseCircle, PrecisePathElement};
+use plotters_piet::{KurboShape, PietBitmapBackend};
+
+/// How far the pixels may differ from the reference.
+#[derive(Debug, Clone, Copy)]
+struct Tolerance {
+    /// The largest difference of a color or alpha channel that still counts as equal.
+    channel: u8,
+    /// The number of pixels that may differ by more than that.
+    pixels: usize,
+}
+
+/// Absorbs rounding differences in the antialiasing of the platform libraries.
+const STRICT: Tolerance = Tolerance {
+    channel: 2,
+    pixels: 0,
+};
+
+/// Text also depends on the installed fonts and the hinting of the text library.
+const TEXT: Tolerance = Tolerance {
+    channel: 48,
+    pixels: 40,
+};
+
+const SIZE: (u32, u32) = (24, 24);
+
+/// Draw on a white bitmap of the given size and compare it with the reference image.
+fn assert_golden(
+    name: &str,
+    size: (u32, u32),
+    tolerance: Tolerance,
+    draw: impl FnOnce(&mut PietBitmapBackend),
+) {
+    let mut backend = PietBitmapBackend::new(size).unwrap();
+    backend
+        .draw_rect((0, 0), (size.0 as i32, size.1 as i32), &WHITE, true)
+        .unwrap();
+    draw(&mut backend);
+    backend.present().unwrap();
+    let actual = backend.into_rgba_buffer().unwrap();
+
+    let reference = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"));
+    let bless = std::env::var_os("PLOTTERS_PIET_BLESS").is_some();
+    if !reference.exists() {
+        if bless {
+            write_png(&reference, size, &actual);
+            eprintln!("created reference image {}", reference.display());
+            return;
+        }
+        panic!(
+            "golden image `{name}` has no reference {}, \
+             run with PLOTTERS_PIET_BLESS=1 to create it",
+            reference.display()
+        );
+    }
+
+    let (expected_size, expected) = read_png(&reference);
+    if expected_size != size {
+        if bless {
+            write_png(&reference, size, &actual);
+            return;
+        }
+        panic!(
+            "golden image `{name}` is {}x{}, but {} is {}x{}",
+            size.0,
+            size.1,
+            reference.display(),
+            expected_size.0,
+            expected_size.1,
+        );
+    }
+
+    let diff = compare(&expected, &actual, size.0, tolerance.channel);
+    if diff.pixels.len() <= tolerance.pixels {
+        return;
+    }
+    if bless {
+        write_png(&reference, size, &actual);
+        return;
+    }
+
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let actual_path = out_dir.join(format!("{name}.actual.png"));
+    let diff_path = out_dir.join(format!("{name}.diff.png"));
+    write_png(&actual_path, size, &actual);
+    write_png(&diff_path, size, &diff.image);
+
+    let (x, y) = diff.pixels[0];
+    panic!(
+        "golden image `{name}` differs from {} in {} of {} pixels \
+         (at most {} allowed), the largest difference is {}, the first one at ({x}, {y})\n\
+         actual output: {}\n\
+         differing pixels: {}\n\
+         run with PLOTTERS_PIET_BLESS=1 to accept the new output",
+        reference.display(),
+        diff.pixels.len(),
+        size.0 * size.1,
+        tolerance.pixels,
+        diff.max_channel_difference,
+        actual_path.display(),
+        diff_path.display(),
+    );
+}
+
+struct Diff {
+    /// The coordinates of the pixels that differ by more than the tolerance.
+    pixels: Vec<(u32, u32)>,
+    max_channel_difference: u8,
+    /// The expected image faded out, with the differing pixels in red.
+    image: Vec<u8>,
+}
+
+fn compare(expected: &[u8], actual: &[u8], width: u32, channel_tolerance: u8) -> Diff {
+    let mut diff = Diff {
+        pixels: Vec::new(),
+        max_channel_difference: 0,
+        image: Vec::with_capacity(expected.len()),
+    };
+
+    let pixels = expected.chunks_exact(4).zip(actual.chunks_exact(4));
+    for (i, (e, a)) in pixels.enumerate() {
+        let difference = e.iter().zip(a).map(|(e, a)| e.abs_diff(*a)).max().unwrap();
+        diff.max_channel_difference = diff.max_channel_difference.max(difference);
+
+        if difference > channel_tolerance {
+            diff.pixels.push((i as u32 % width, i as u32 / width));
+            diff.image.extend([255, 0, 0, 255]);
+        } else {
+            let gray = e[..3].iter().map(|&c| c as u32).sum::<u32>() / 3;
+            let faded = (255 - (255 - gray) / 4) as u8;
+            diff.image.extend([faded, faded, faded, 255]);
+        }
+    }
+    diff
+}
+
+fn read_png(path: &Path) -> ((u32, u32), Vec<u8>) {
+    let decoder = png::Decoder::new(File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight),
+        "{} has to be an 8 bit RGBA image",
+        path.display()
+    );
+    buf.truncate(info.buffer_size());
+    ((info.width, info.height), buf)
+}
+
+fn write_png(path: &Path, (width, height): (u32, u32), data: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path).unwrap()), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(data).unwrap();
+}
+
+fn black() -> BackendColor {
+    BLACK.to_backend_color()
+}
+
+#[test]
+fn compare_reports_differing_pixels() {
+    let expected = [0, 0, 0, 255, 10, 10, 10, 255, 0, 0, 0, 255];
+    let actual = [1, 0, 0, 255, 10, 10, 10, 255, 0, 0, 200, 255];
+    let diff = compare(&expected, &actual, 2, 2);
+    assert_eq!(diff.pixels, [(0, 1)]);
+    assert_eq!(diff.max_channel_difference, 200);
+    assert_eq!(diff.image[8..], [255, 0, 0, 255]);
+}
+
+#[test]
+fn pixel() {
+    assert_golden("pixel", SIZE, STRICT, |backend| {
+        backend.draw_pixel((0, 0), black()).unwrap();
+        backend.draw_pixel((5, 7), RED.to_backend_color()).unwrap();
+        backend
+            .draw_pixel((23, 23), BLUE.mix(0.5).to_backend_color())
+            .unwrap();
+    });
+}
+
+#[test]
+fn line() {
+    assert_golden("line", SIZE, STRICT, |backend| {
+        backend.draw_line((2, 2), (21, 2), &BLACK).unwrap();
+        backend.draw_line((2, 5), (2, 21), &BLACK).unwrap();
+        backend
+            .draw_line((5, 5), (21, 21), &RED.stroke_width(3))
+            .unwrap();
+    });
+}
+
+#[test]
+fn rect_outline() {
+    assert_golden("rect_outline", SIZE, STRICT, |backend| {
+        backend.draw_rect((2, 2), (21, 12), &BLACK, false).unwrap();
+        backend
+            .draw_rect((5, 15), (18, 21), &BLUE.stroke_width(2), false)
+            .unwrap();
+    });
+}
+
+#[test]
+fn rect_fill() {
+    assert_golden("rect_fill", SIZE, STRICT, |backend| {
+        backend.draw_rect((2, 2), (21, 12), &BLACK, true).unwrap();
+        backend
+            .draw_rect((5, 15), (18, 21), &GREEN.mix(0.5), true)
+            .unwrap();
+    });
+}
+
+#[test]
+fn circle() {
+    assert_golden("circle", SIZE, STRICT, |backend| {
+        backend.draw_circle((7, 7), 5, &BLACK, false).unwrap();
+        backend.draw_circle((16, 16), 6, &RED, true).unwrap();
+    });
+}
+
+#[test]
+fn polygon() {
+    assert_golden("polygon", SIZE, STRICT, |backend| {
+        backend
+            .fill_polygon([(2, 21), (12, 2), (21, 21)], &BLUE)
+            .unwrap();
+    });
+}
+
+#[test]
+fn path() {
+    assert_golden("path", SIZE, STRICT, |backend| {
+        backend
+            .draw_path([(2, 21), (8, 4), (14, 18), (21, 2)], &BLACK.stroke_width(2))
+            .unwrap();
+    });
+}
+
+#[test]
+fn stroke_style() {
+    assert_golden("stroke_style", SIZE, STRICT, |backend| {
+        backend.set_stroke_style(StrokeStyle::new().line_join(LineJoin::Round));
+        backend
+            .draw_path([(2, 12), (8, 3), (14, 12)], &BLACK.stroke_width(3))
+            .unwrap();
+        backend.set_stroke_style(StrokeStyle::new().line_cap(LineCap::Butt));
+        backend
+            .draw_line((2, 16), (21, 16), &RED.stroke_width(2))
+            .unwrap();
+        backend.set_stroke_style(StrokeStyle::new().dash_pattern(&[3., 2.]));
+        backend.draw_line((2, 20), (21, 20), &BLUE).unwrap();
+    });
+}
+
+#[test]
+fn precise() {
+    assert_golden("precise", SIZE, STRICT, |backend| {
+        let root = backend.into_drawing_area();
+        let chart = ChartBuilder::on(&root)
+            .build_cartesian_2d(0.0..1.0, 0.0..1.0)
+            .unwrap();
+        chart
+            .draw_precise(&PrecisePathElement::new([(0.1, 0.3), (0.9, 0.45)], BLACK))
+            .unwrap();
+        chart
+            .draw_precise(&PreciseCircle::new((0.52, 0.73), 3.5, RED.filled()))
+            .unwrap();
+    });
+}
+
+#[test]
+fn kurbo_shape() {
+    assert_golden("kurbo_shape", SIZE, STRICT, |backend| {
+        let root = backend.into_drawing_area();
+        let chart = ChartBuilder::on(&root)
+            .build_cartesian_2d(0.0..1.0, 0.0..1.0)
+            .unwrap();
+        let rect = RoundedRect::new(0.1, 0.55, 0.9, 0.9, 0.1);
+        chart
+            .draw_precise(
+                &KurboShape::new(rect)
+                    .with_fill(GREEN.mix(0.5))
+                    .with_stroke(BLACK),
+            )
+            .unwrap();
+        let curve = CubicBez::new((0.05, 0.05), (0.3, 0.6), (0.6, -0.2), (0.95, 0.4));
+        chart
+            .draw_precise(&KurboShape::new(curve).with_stroke(RED.stroke_width(2)))
+            .unwrap();
+        let arc = Arc {
+            center: (0.5, 0.25).into(),
+            radii: (0.2, 0.2).into(),
+            start_angle: 0.,
+            sweep_angle: std::f64::consts::PI,
+            x_rotation: 0.,
+        };
+        chart
+            .draw_precise(&KurboShape::new(arc).with_stroke(BLUE))
+            .unwrap();
+    });
+}
+
+#[test]
+fn text() {
+    assert_golden("text", (48, 24), TEXT, |backend| {
+        let style = TextStyle::from(("sans-serif", 20).into_font())
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        backend.draw_text("Ag", &style, (24, 12)).unwrap();
+    });
+}
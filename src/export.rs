@@ -0,0 +1,108 @@
+//! Commands to save plots to files.
+
+use std::path::PathBuf;
+
+use druid::{Selector, Size};
+
+/// Save the plot as a PNG image.
+///
+/// When the plot widget receives this command, it runs the plot closure with the current
+/// data once more, draws into an offscreen image and writes that to the given path. The
+/// closure gets the same size and [`Env`](druid::Env) as on screen, so the image shows the
+/// same plot. Errors are reported to the callback registered with
+/// [`Plot::on_error`](crate::Plot::on_error).
+///
+/// Send the command to the [`WidgetId`](druid::WidgetId) of the plot, otherwise every plot
+/// in the window would save itself to the same path.
+///
+/// ```rust
+/// # use druid::{EventCtx, WidgetId};
+/// use plotters_druid::export::{PngExport, SAVE_PLOT_PNG};
+///
+/// fn save_plot(ctx: &mut EventCtx, plot_id: WidgetId) {
+///     let export = PngExport::new("plot.png").with_scale(2.);
+///     ctx.submit_command(SAVE_PLOT_PNG.with(export).to(plot_id));
+/// }
+/// ```
+pub const SAVE_PLOT_PNG: Selector<PngExport> = Selector::new("plotters-druid.save-plot-png");
+
+/// The payload of [`SAVE_PLOT_PNG`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngExport {
+    pub(crate) path: PathBuf,
+    pub(crate) size: Option<Size>,
+    pub(crate) scale: f64,
+}
+
+impl PngExport {
+    /// Save the plot to `path`, with the size that the widget currently has.
+    pub fn new(path: impl Into<PathBuf>) -> PngExport {
+        PngExport {
+            path: path.into(),
+            size: None,
+            scale: 1.,
+        }
+    }
+
+    /// Builder-style method to draw the plot with a different size.
+    ///
+    /// The size is given in logical pixels, like the size of the widget.
+    pub fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// Builder-style method to set the number of pixels in the image per logical pixel.
+    ///
+    /// With a scale of 2, the image has twice the width and height of the widget, just like
+    /// the plot on a HiDPI display. Defaults to 1.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Save the plot as an SVG file.
+///
+/// This works like [`SAVE_PLOT_PNG`], except that the plot closure draws on the render
+/// context of piet-svg, so the result is a vector graphic. Bitmaps that the closure draws
+/// are embedded as images.
+///
+/// ```rust
+/// # use druid::{EventCtx, WidgetId};
+/// use plotters_druid::export::{SvgExport, SAVE_PLOT_SVG};
+///
+/// fn save_plot(ctx: &mut EventCtx, plot_id: WidgetId) {
+///     let export = SvgExport::new("plot.svg").with_size((800., 600.));
+///     ctx.submit_command(SAVE_PLOT_SVG.with(export).to(plot_id));
+/// }
+/// ```
+#[cfg(feature = "svg")]
+pub const SAVE_PLOT_SVG: Selector<SvgExport> = Selector::new("plotters-druid.save-plot-svg");
+
+/// The payload of [`SAVE_PLOT_SVG`].
+#[cfg(feature = "svg")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgExport {
+    pub(crate) path: PathBuf,
+    pub(crate) size: Option<Size>,
+}
+
+#[cfg(feature = "svg")]
+impl SvgExport {
+    /// Save the plot to `path`, with the size that the widget currently has.
+    pub fn new(path: impl Into<PathBuf>) -> SvgExport {
+        SvgExport {
+            path: path.into(),
+            size: None,
+        }
+    }
+
+    /// Builder-style method to draw the plot with a different size.
+    ///
+    /// The size is given in logical pixels, like the size of the widget.
+    pub fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+}
@@ -0,0 +1,112 @@
+//! Sizing of the plot widget.
+
+use druid::{BoxConstraints, Size};
+
+/// The size that is used for the plot if the constraints are unbounded and no preferred
+/// size has been given.
+const DEFAULT_SIZE: Size = Size::new(400., 300.);
+
+/// How the plot widget chooses its size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PlotLayout {
+    /// Used in directions in which the constraints are unbounded.
+    pub preferred_size: Size,
+    /// Width divided by height.
+    pub aspect_ratio: Option<f64>,
+    pub min_size: Size,
+    pub max_size: Size,
+}
+
+impl Default for PlotLayout {
+    fn default() -> Self {
+        PlotLayout {
+            preferred_size: DEFAULT_SIZE,
+            aspect_ratio: None,
+            min_size: Size::ZERO,
+            max_size: Size::new(f64::INFINITY, f64::INFINITY),
+        }
+    }
+}
+
+impl PlotLayout {
+    /// Take as much space as possible within the limits, falling back to the preferred size
+    /// if there is no limit.
+    pub fn size(&self, bc: &BoxConstraints) -> Size {
+        let max = Size::new(
+            bc.max().width.min(self.max_size.width),
+            bc.max().height.min(self.max_size.height),
+        );
+        let width = max.width.is_finite().then_some(max.width);
+        let height = max.height.is_finite().then_some(max.height);
+
+        let size = match (self.aspect_ratio, width, height) {
+            (None, width, height) => Size::new(
+                width.unwrap_or(self.preferred_size.width),
+                height.unwrap_or(self.preferred_size.height),
+            ),
+            (Some(ratio), Some(width), Some(height)) => {
+                let width = width.min(height * ratio);
+                Size::new(width, width / ratio)
+            }
+            (Some(ratio), Some(width), None) => Size::new(width, width / ratio),
+            (Some(ratio), None, Some(height)) => Size::new(height * ratio, height),
+            (Some(ratio), None, None) => {
+                Size::new(self.preferred_size.width, self.preferred_size.width / ratio)
+            }
+        };
+
+        bc.constrain(size.clamp(self.min_size, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INF: f64 = f64::INFINITY;
+
+    fn bc(width: f64, height: f64) -> BoxConstraints {
+        BoxConstraints::new(Size::ZERO, Size::new(width, height))
+    }
+
+    #[test]
+    fn fill_bounded_constraints() {
+        let layout = PlotLayout::default();
+        assert_eq!(layout.size(&bc(200., 100.)), Size::new(200., 100.));
+    }
+
+    #[test]
+    fn unbounded_falls_back_to_preferred_size() {
+        let layout = PlotLayout::default();
+        assert_eq!(layout.size(&bc(200., INF)), Size::new(200., 300.));
+        assert_eq!(layout.size(&bc(INF, INF)), DEFAULT_SIZE);
+    }
+
+    #[test]
+    fn aspect_ratio() {
+        let layout = PlotLayout {
+            aspect_ratio: Some(2.),
+            ..Default::default()
+        };
+        assert_eq!(layout.size(&bc(200., 200.)), Size::new(200., 100.));
+        assert_eq!(layout.size(&bc(200., 50.)), Size::new(100., 50.));
+        assert_eq!(layout.size(&bc(200., INF)), Size::new(200., 100.));
+        assert_eq!(layout.size(&bc(INF, 50.)), Size::new(100., 50.));
+        assert_eq!(layout.size(&bc(INF, INF)), Size::new(400., 200.));
+    }
+
+    #[test]
+    fn min_and_max_size() {
+        let layout = PlotLayout {
+            min_size: Size::new(50., 50.),
+            max_size: Size::new(150., 150.),
+            ..Default::default()
+        };
+        assert_eq!(layout.size(&bc(200., 20.)), Size::new(150., 20.));
+        assert_eq!(layout.size(&bc(INF, INF)), Size::new(150., 150.));
+
+        // The constraints of the parent win over the minimum size.
+        let tight = BoxConstraints::tight(Size::new(30., 30.));
+        assert_eq!(layout.size(&tight), Size::new(30., 30.));
+    }
+}
@@ -12,7 +12,8 @@ Note that this is not directly a plotters backend in the sense described in
 [plotters_backend](https://docs.rs/plotters-backend/latest/plotters_backend/), instead this uses
 the plotters-piet backend and wraps it in a struct that implements [`druid::Widget`].
 
-You'll mainly need [`Plot::new`] from this crate.
+You'll mainly need [`Plot::new`] or [`Plot::try_new`] from this crate. To follow the theme of the
+application, use [`Plot::with_ctx`] which gives the closure access to the druid [`Env`].
 
 # Example
 
@@ -40,27 +41,91 @@ let main_window = WindowDesc::new(build_plot_widget());
 # }
 ```
 
-# Limitations
+# Errors
 
-It's currently not possible to propagate errors that might be returned from the plotters API. Right now
-this means you'll probably have to use `.unwrap()` a lot in the closure that you pass to [`Plot::new`],
-or alternatively just log it and shrug.
+The plotters API returns a `Result` from pretty much every call. With [`Plot::new`] you'll have to
+`.unwrap()` those, which brings down the whole app if anything goes wrong. [`Plot::try_new`] instead
+takes a closure that returns a `Result`, so you can just use `?`. When the closure fails, the widget
+draws an error placeholder instead (see [`Plot::with_error_placeholder`]) and passes the error to the
+callback registered with [`Plot::on_error`]. That callback gets mutable access to the data, so the
+rest of the application can be notified of the error.
 
-The possible errors in there mostly come from the drawing backend, e.g. cairo / direct2d / whatever piet
-uses on your platform. Just directly propagating these in the widget's draw function doesn't really make
-sense because it's not clear what druid is supposed to do with these errors. Ideally we'd probably change
-something in the data to notify the rest of the application of the error. If anyone has a good suggestion
-for a possible API feel free to open an issue.
+# Interactive plots
+
+With [`Plot::interactive`] the user can zoom into a cartesian plot with the mouse wheel, pan it by
+dragging, and get back to the initial view with a double-click. The closure then gets a [`PlotCtx`]
+instead of the size, which holds the ranges that should currently be shown.
+
+# Mouse position in data coordinates
+
+If the closure passes its chart to [`PlotCtx::set_coord_mapping`], the widget can translate
+positions in the widget to the data coordinates of the chart, see [`Plot::to_data`]. With
+[`Plot::on_hover`] the application is notified of the data coordinates under the mouse cursor.
+
+# Tooltips
+
+Points that the closure registers with [`PlotCtx::add_tooltip_series`] get a tooltip when the mouse
+hovers close to them. The tooltip shows the series name and the coordinates of the nearest point,
+see [`Plot::with_tooltip_format`] to change the text. While only the tooltip moves, the widget
+redraws a snapshot of the plot instead of running the closure again.
+
+# Theming
+
+The plot closure of [`Plot::with_ctx`] can follow the druid theme with [`PlotCtx::color`]. For a
+consistent look across plots, the [`theme`] module has `Env` keys for the plot background, axes,
+grid, labels and series colors. Set them with `env_scope` and use the styles from
+[`PlotCtx::theme`] in the closure.
+
+# Layout
+
+By default the plot fills all the space that the parent widget allows. Use
+[`Plot::with_preferred_size`], [`Plot::with_aspect_ratio`], [`Plot::with_min_size`] and
+[`Plot::with_max_size`] to change that, e.g. when the plot is put in a scroll area.
+
+# Saving plots
+
+To save a plot as a PNG image, send the [`SAVE_PLOT_PNG`](export::SAVE_PLOT_PNG) command to the
+plot widget. The plot is then drawn once more with the current data and written to the file.
+With the `svg` feature, [`SAVE_PLOT_SVG`](export::SAVE_PLOT_SVG) does the same for vector graphics.
+
+# Performance
+
+The closure runs every time the widget is painted. For plots with many points this can make the
+whole application sluggish, in that case enable [`Plot::with_cache`] to only run the closure when
+the data or the size of the widget changes.
+If the data is a large application state of which the plot only shows a part, use
+[`Plot::with_redraw_selector`] so that unrelated changes don't cause the plot to be redrawn.
 
 */
 
-use druid::{Data, Widget};
+use std::{error::Error, ops::Range};
+
+use druid::{
+    piet::{Device, InterpolationMode, Piet, PietImage},
+    Data, Env, EventCtx, ExtEventSink, Key, PaintCtx, Point, Rect, RenderContext, Selector, Size,
+    Target, Widget, WidgetId,
+};
 use plotters::{
-    coord::Shift,
+    coord::{CoordTranslate, ReverseCoordTranslate, Shift},
     prelude::{DrawingArea, IntoDrawingArea},
+    style::RGBAColor,
 };
 use plotters_piet::PietBackend;
 
+pub mod export;
+mod layout;
+pub mod theme;
+mod tooltip;
+mod view;
+
+use export::{PngExport, SAVE_PLOT_PNG};
+#[cfg(feature = "svg")]
+use export::{SvgExport, SAVE_PLOT_SVG};
+use layout::PlotLayout;
+use theme::PlotTheme;
+use tooltip::{Hover, HoverSeries};
+use view::CartesianView;
+
 /// The type of a plot widget.
 ///
 /// See [`Plot::new`] for information on how to construct this.
@@ -81,9 +146,226 @@ use plotters_piet::PietBackend;
 /// ```
 pub struct Plot<T: Data> {
     #[allow(clippy::type_complexity)]
-    plot: Box<dyn Fn((u32, u32), &T, &DrawingArea<PietBackend, Shift>)>,
+    plot: Box<dyn Fn(&mut PlotCtx, &T, &DrawingArea<PietBackend, Shift>) -> PlotResult>,
+    /// `None` draws the error message, see [`Plot::with_error_placeholder`].
+    #[allow(clippy::type_complexity)]
+    error_placeholder:
+        Option<Box<dyn Fn((u32, u32), &dyn Error, &DrawingArea<PietBackend, Shift>)>>,
+    #[allow(clippy::type_complexity)]
+    on_error: Option<Box<dyn Fn(&mut EventCtx, &mut T, &dyn Error)>>,
+    /// Errors only show up while painting, where we have neither mutable access to the
+    /// data nor a way to submit commands. We therefore keep the error here and send a
+    /// command to ourselves to report it in `event`.
+    pending_error: Option<Box<dyn Error>>,
+    last_error_message: Option<String>,
+    ext_event_sink: Option<ExtEventSink>,
+    /// The visible ranges if this is an interactive plot.
+    view: Option<CartesianView>,
+    /// The last mouse position while the view is being dragged.
+    drag_pos: Option<Point>,
+    /// Whether the drawing area is measured in device pixels, see
+    /// [`Plot::with_device_pixels`].
+    device_pixels: bool,
+    /// Plotters pixels per logical pixel, as of the last paint.
+    scale: f64,
+    coord_mapping: Option<CoordMapping>,
+    #[allow(clippy::type_complexity)]
+    on_hover: Option<Box<dyn Fn(&mut EventCtx, &mut T, Option<(f64, f64)>)>>,
+    tooltip_series: Vec<HoverSeries>,
+    #[allow(clippy::type_complexity)]
+    tooltip_format: Box<dyn Fn(&str, (f64, f64)) -> String>,
+    hover: Option<Hover>,
+    /// Decides whether a change of the data requires the plot to be drawn again.
+    #[allow(clippy::type_complexity)]
+    needs_redraw: Box<dyn Fn(&T, &T) -> bool>,
+    layout: PlotLayout,
+    /// Whether the plot is always drawn offscreen, see [`Plot::with_cache`].
+    cache: bool,
+    /// The plot as it was last drawn. With [`Plot::with_cache`] every paint reuses it,
+    /// otherwise only the paints that just move the tooltip do.
+    snapshot: Option<Snapshot>,
+    /// Whether the tooltip changed since the last paint.
+    tooltip_changed: bool,
+}
+
+/// Maps between plotters pixels and the data coordinates of a chart.
+type CoordMapping = Box<dyn ReverseCoordTranslate<From = (f64, f64)>>;
+
+/// A copy of what the plot closure drew, in device pixels.
+struct Snapshot {
+    image: PietImage,
+    size: Size,
+    scale: f64,
+}
+
+/// The return type of the closure passed to [`Plot::try_new`].
+pub type PlotResult = Result<(), Box<dyn Error>>;
+
+/// Information that is passed to the closure of a plot, and that the closure can pass
+/// back to the widget.
+///
+/// See [`Plot::with_ctx`] and [`Plot::interactive`] for how this is used.
+pub struct PlotCtx {
+    size: (u32, u32),
+    scale: f64,
+    env: Env,
+    theme: PlotTheme,
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+    plotting_area: Option<(Range<i32>, Range<i32>)>,
+    coord_mapping: Option<CoordMapping>,
+    tooltip_series: Vec<HoverSeries>,
 }
 
+impl std::fmt::Debug for PlotCtx {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("PlotCtx")
+            .field("size", &self.size)
+            .field("scale", &self.scale)
+            .field("theme", &self.theme)
+            .field("x_range", &self.x_range)
+            .field("y_range", &self.y_range)
+            .field("plotting_area", &self.plotting_area)
+            .field("coord_mapping", &self.coord_mapping.is_some())
+            .field("tooltip_series", &self.tooltip_series.len())
+            .finish()
+    }
+}
+
+impl PlotCtx {
+    /// The width and height of the widget in logical pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// The number of pixels of the drawing area per logical pixel.
+    ///
+    /// This is 1, unless the plot is drawn in device pixels with
+    /// [`Plot::with_device_pixels`]. Then it is the scale factor of the display, multiply
+    /// fixed sizes like font sizes, margins and label area sizes with it to keep them in
+    /// logical pixels.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// let plot = Plot::with_ctx(|ctx, _: &(), root| {
+    ///     let scale = ctx.scale();
+    ///     let mut chart = ChartBuilder::on(&root)
+    ///         .caption("y = x", ("sans-serif", 20. * scale))
+    ///         .margin(5. * scale)
+    ///         .x_label_area_size(30. * scale)
+    ///         .build_cartesian_2d(0.0..1.0, 0.0..1.0)?;
+    ///     Ok(())
+    /// })
+    /// .with_device_pixels(true);
+    /// ```
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The [`Env`] that the widget is painted with.
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Look up a color in the [`Env`] and convert it for use with plotters.
+    ///
+    /// This makes it easy to follow the theme of the application:
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// let plot = Plot::with_ctx(|ctx, _: &(), root| {
+    ///     let text_color = ctx.color(druid::theme::TEXT_COLOR);
+    ///     let mut chart = ChartBuilder::on(&root)
+    ///         .x_label_area_size(30)
+    ///         .build_cartesian_2d(0.0..1.0, 0.0..1.0)?;
+    ///     chart
+    ///         .configure_mesh()
+    ///         .axis_style(text_color)
+    ///         .x_label_style(("sans-serif", 16).into_font().color(&text_color))
+    ///         .draw()?;
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn color(&self, key: Key<druid::Color>) -> RGBAColor {
+        theme::to_plotters_color(&self.env.get(key))
+    }
+
+    /// Ready-to-use plotters styles, set through the keys in [`theme`].
+    pub fn theme(&self) -> &PlotTheme {
+        &self.theme
+    }
+
+    /// The x range that should currently be visible in an interactive plot.
+    pub fn x_range(&self) -> Range<f64> {
+        self.x_range.clone()
+    }
+
+    /// The y range that should currently be visible in an interactive plot.
+    pub fn y_range(&self) -> Range<f64> {
+        self.y_range.clone()
+    }
+
+    /// Tell the widget where in the drawing area the chart is drawn.
+    ///
+    /// Pass `chart.plotting_area()` here, so that zooming and panning can track the cursor
+    /// exactly. Without this, the ranges are assumed to span the whole widget, which is
+    /// a bit off if the chart has margins or label areas. [`PlotCtx::set_coord_mapping`]
+    /// does this as well.
+    pub fn set_plotting_area<CT: CoordTranslate>(&mut self, area: &DrawingArea<PietBackend, CT>) {
+        self.plotting_area = Some(area.get_pixel_range());
+    }
+
+    /// Tell the widget how positions in the widget map to data coordinates.
+    ///
+    /// Pass `chart.plotting_area()` here to enable [`Plot::to_data`] and [`Plot::on_hover`].
+    /// Only charts with `f64` coordinates are supported, e.g. charts built with
+    /// `build_cartesian_2d(0.0..1.0, 0.0..1.0)`. This also sets the plotting area, see
+    /// [`PlotCtx::set_plotting_area`].
+    pub fn set_coord_mapping<CT>(&mut self, area: &DrawingArea<PietBackend, CT>)
+    where
+        CT: ReverseCoordTranslate<From = (f64, f64)> + Clone + 'static,
+    {
+        self.set_plotting_area(area);
+        self.coord_mapping = Some(Box::new(area.as_coord_spec().clone()));
+    }
+
+    /// Register points that should get a tooltip when the mouse hovers close to them.
+    ///
+    /// The points are given in data coordinates, usually the same ones that are passed to
+    /// `chart.draw_series`. This only works if the chart has also been passed to
+    /// [`PlotCtx::set_coord_mapping`].
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// let plot = Plot::interactive(0.0..1.0, 0.0..1.0, |ctx, _: &(), root| {
+    ///     let mut chart =
+    ///         ChartBuilder::on(&root).build_cartesian_2d(ctx.x_range(), ctx.y_range())?;
+    ///     ctx.set_coord_mapping(chart.plotting_area());
+    ///
+    ///     let points = vec![(0.2, 0.4), (0.5, 0.6), (0.8, 0.3)];
+    ///     ctx.add_tooltip_series("measurements", points.iter().copied());
+    ///     chart.draw_series(points.iter().map(|&p| Circle::new(p, 3, BLUE.filled())))?;
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn add_tooltip_series(
+        &mut self,
+        name: impl Into<String>,
+        points: impl IntoIterator<Item = (f64, f64)>,
+    ) {
+        self.tooltip_series.push(HoverSeries {
+            name: name.into(),
+            points: points.into_iter().collect(),
+        });
+    }
+}
+
+/// Sent by the widget to itself to report an error that happened while painting.
+const REPORT_ERROR: Selector = Selector::new("plotters-druid.report-error");
+
 impl<T: Data> Plot<T> {
     /// Create a plot widget
     ///
@@ -91,6 +373,9 @@ impl<T: Data> Plot<T> {
     /// The function has access to the width and height of the plotting area, to the
     /// [`Data`] of the rust widget, and to a plotters [`DrawingArea`].
     ///
+    /// The width and height and the drawing area are measured in druid's logical pixels.
+    /// See [`Plot::with_device_pixels`] to draw at the full resolution of HiDPI displays.
+    ///
     /// ```rust
     /// # use plotters_druid::Plot;
     /// # use plotters::prelude::*;
@@ -106,7 +391,450 @@ impl<T: Data> Plot<T> {
     /// });
     /// ```
     pub fn new(f: impl Fn((u32, u32), &T, &DrawingArea<PietBackend, Shift>) + 'static) -> Plot<T> {
-        Plot { plot: Box::new(f) }
+        Plot::try_new(move |size, data, root| {
+            f(size, data, root);
+            Ok(())
+        })
+    }
+
+    /// Create a plot widget from a closure that can fail
+    ///
+    /// This works just like [`Plot::new`], except that the closure returns a [`PlotResult`].
+    /// This lets you use `?` on the plotters API calls instead of unwrapping them.
+    ///
+    /// If the closure returns an error, the error placeholder is drawn on top of whatever
+    /// the closure managed to draw before failing, and the error is reported to the
+    /// [`on_error`](Plot::on_error) callback.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// # #[derive(Clone, druid::Data)]
+    /// # struct AppState;
+    /// Plot::try_new(|(width, height), data: &AppState, root| {
+    ///     root.fill(&WHITE)?;
+    ///     let mut chart = ChartBuilder::on(&root)
+    ///         .build_cartesian_2d(-1f32..1f32, -0.1f32..1f32)?;
+    ///
+    ///     // see the plotters documentation on how to use `chart`
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn try_new(
+        f: impl Fn((u32, u32), &T, &DrawingArea<PietBackend, Shift>) -> PlotResult + 'static,
+    ) -> Plot<T> {
+        Plot::with_ctx(move |ctx, data, root| f(ctx.size(), data, root))
+    }
+
+    /// Create an interactive cartesian plot widget
+    ///
+    /// The user can zoom in and out with the mouse wheel, pan the view by dragging with
+    /// the left mouse button, and double-click to get back to `x_range` and `y_range`.
+    ///
+    /// Instead of the size, the closure gets a [`PlotCtx`], from which it should take the
+    /// ranges for the chart. Passing the plotting area of the chart back through the
+    /// [`PlotCtx`] makes sure that the zoom happens around the cursor.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// # #[derive(Clone, druid::Data)]
+    /// # struct AppState;
+    /// Plot::interactive(-1.0..1.0, -0.1..1.0, |ctx, data: &AppState, root| {
+    ///     root.fill(&WHITE)?;
+    ///     let mut chart = ChartBuilder::on(&root)
+    ///         .x_label_area_size(30)
+    ///         .y_label_area_size(30)
+    ///         .build_cartesian_2d(ctx.x_range(), ctx.y_range())?;
+    ///     ctx.set_plotting_area(chart.plotting_area());
+    ///
+    ///     chart.configure_mesh().draw()?;
+    ///     // draw the series as usual
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn interactive(
+        x_range: Range<f64>,
+        y_range: Range<f64>,
+        f: impl Fn(&mut PlotCtx, &T, &DrawingArea<PietBackend, Shift>) -> PlotResult + 'static,
+    ) -> Plot<T> {
+        let mut plot = Plot::with_ctx(f);
+        plot.view = Some(CartesianView::new(x_range, y_range));
+        plot
+    }
+
+    /// Create a plot widget whose closure gets a [`PlotCtx`]
+    ///
+    /// This works like [`Plot::try_new`], except that the closure gets a [`PlotCtx`] instead
+    /// of just the size. Through it, the closure has access to the [`Env`] of the widget,
+    /// e.g. to use the colors of the current theme, see [`PlotCtx::color`].
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// # #[derive(Clone, druid::Data)]
+    /// # struct AppState;
+    /// Plot::with_ctx(|ctx, data: &AppState, root| {
+    ///     root.fill(&ctx.color(druid::theme::BACKGROUND_DARK))?;
+    ///     let mut chart = ChartBuilder::on(&root)
+    ///         .build_cartesian_2d(-1f32..1f32, -0.1f32..1f32)?;
+    ///
+    ///     // see the plotters documentation on how to use `chart`
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn with_ctx(
+        f: impl Fn(&mut PlotCtx, &T, &DrawingArea<PietBackend, Shift>) -> PlotResult + 'static,
+    ) -> Plot<T> {
+        Plot {
+            plot: Box::new(f),
+            error_placeholder: None,
+            on_error: None,
+            pending_error: None,
+            last_error_message: None,
+            ext_event_sink: None,
+            view: None,
+            drag_pos: None,
+            device_pixels: false,
+            scale: 1.,
+            coord_mapping: None,
+            on_hover: None,
+            tooltip_series: Vec::new(),
+            tooltip_format: Box::new(tooltip::default_format),
+            hover: None,
+            needs_redraw: Box::new(|old_data: &T, data: &T| !old_data.same(data)),
+            layout: PlotLayout::default(),
+            cache: false,
+            snapshot: None,
+            tooltip_changed: false,
+        }
+    }
+
+    /// Builder-style method to set what is drawn when the plot closure fails
+    ///
+    /// The placeholder is drawn with the same size and drawing area that was passed to
+    /// the plot closure. By default the error message is printed in the middle of the
+    /// widget.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// let plot = Plot::try_new(|_, _: &(), root| {
+    ///     // ...
+    ///     # Ok(())
+    /// })
+    /// .with_error_placeholder(|_, _, root| {
+    ///     root.fill(&RED.mix(0.2)).ok();
+    /// });
+    /// ```
+    pub fn with_error_placeholder(
+        mut self,
+        f: impl Fn((u32, u32), &dyn Error, &DrawingArea<PietBackend, Shift>) + 'static,
+    ) -> Self {
+        self.error_placeholder = Some(Box::new(f));
+        self
+    }
+
+    /// Builder-style method to set a callback that is notified of errors in the plot closure
+    ///
+    /// The callback is called from [`Widget::event`] shortly after the failed paint, so it can
+    /// modify the data or submit a [`druid::Command`]. The same error is only reported once,
+    /// it will be reported again after the plot has been drawn successfully in between.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// #[derive(Clone, druid::Data)]
+    /// struct AppState {
+    ///     plot_error: Option<String>,
+    /// }
+    ///
+    /// let plot = Plot::try_new(|_, _: &AppState, root| {
+    ///     // ...
+    ///     # Ok(())
+    /// })
+    /// .on_error(|_ctx, data, error| {
+    ///     data.plot_error = Some(error.to_string());
+    /// });
+    /// ```
+    pub fn on_error(mut self, f: impl Fn(&mut EventCtx, &mut T, &dyn Error) + 'static) -> Self {
+        self.on_error = Some(Box::new(f));
+        self
+    }
+
+    /// Builder-style method to set a callback that gets the data coordinates under the cursor
+    ///
+    /// The callback is called whenever the mouse moves over the widget. The position is
+    /// `None` if the cursor is outside of the chart, or if the closure didn't pass its chart
+    /// to [`PlotCtx::set_coord_mapping`].
+    ///
+    /// Changing the data from the callback makes the plot redraw on every mouse move, use
+    /// [`Plot::with_redraw_selector`] to only redraw it when the plotted part changes.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// # use std::sync::Arc;
+    /// #[derive(Clone, druid::Data)]
+    /// struct AppState {
+    ///     measurements: Arc<Vec<(f64, f64)>>,
+    ///     hover: Option<(f64, f64)>,
+    /// }
+    ///
+    /// let plot = Plot::interactive(0.0..1.0, 0.0..1.0, |ctx, data: &AppState, root| {
+    ///     let chart = ChartBuilder::on(&root).build_cartesian_2d(ctx.x_range(), ctx.y_range())?;
+    ///     ctx.set_coord_mapping(chart.plotting_area());
+    ///     // draw data.measurements
+    ///     Ok(())
+    /// })
+    /// .on_hover(|_ctx, data, pos| data.hover = pos)
+    /// .with_redraw_selector(|data| &data.measurements);
+    /// ```
+    pub fn on_hover(
+        mut self,
+        f: impl Fn(&mut EventCtx, &mut T, Option<(f64, f64)>) + 'static,
+    ) -> Self {
+        self.on_hover = Some(Box::new(f));
+        self
+    }
+
+    /// Builder-style method to set the text of the tooltips
+    ///
+    /// The function gets the name of the series and the coordinates of the hovered point.
+    /// By default, the name is followed by the x and y coordinates on separate lines.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// let plot = Plot::interactive(0.0..1.0, 0.0..1.0, |ctx, _: &(), root| {
+    ///     // ...
+    ///     # Ok(())
+    /// })
+    /// .with_tooltip_format(|name, (x, y)| format!("{name}: {y:.1} °C at {x:.0} s"));
+    /// ```
+    pub fn with_tooltip_format(mut self, f: impl Fn(&str, (f64, f64)) -> String + 'static) -> Self {
+        self.tooltip_format = Box::new(f);
+        self
+    }
+
+    /// Builder-style method to decide which changes of the data cause the plot to be redrawn
+    ///
+    /// By default, the plot is redrawn whenever the data is not [`Data::same`] as before. If
+    /// the plot only shows part of a larger application state, this function can check
+    /// just that part. It gets the old and the new data and returns whether the plot has to
+    /// be drawn again. See [`Plot::with_redraw_selector`] for the common case.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// #[derive(Clone, druid::Data)]
+    /// struct AppState {
+    ///     samples: usize,
+    ///     frequency: f64,
+    ///     search_text: String,
+    /// }
+    ///
+    /// let plot = Plot::new(|_, data: &AppState, root| {
+    ///     // draw with data.samples and data.frequency
+    /// })
+    /// .with_needs_redraw(|old, new| old.samples != new.samples || old.frequency != new.frequency);
+    /// ```
+    pub fn with_needs_redraw(mut self, f: impl Fn(&T, &T) -> bool + 'static) -> Self {
+        self.needs_redraw = Box::new(f);
+        self
+    }
+
+    /// Builder-style method to only redraw the plot when part of the data changes
+    ///
+    /// The selector picks the part of the data that the plot depends on, the plot is only
+    /// redrawn if that part is not [`Data::same`] as before. This is useful when the data of
+    /// the widget is a large application state, so that for example typing in an unrelated
+    /// text box doesn't redraw the plot every time.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use std::sync::Arc;
+    /// #[derive(Clone, druid::Data)]
+    /// struct AppState {
+    ///     measurements: Arc<Vec<(f64, f64)>>,
+    ///     search_text: String,
+    /// }
+    ///
+    /// let plot = Plot::new(|_, data: &AppState, root| {
+    ///     // draw data.measurements
+    /// })
+    /// .with_redraw_selector(|data| &data.measurements);
+    /// ```
+    pub fn with_redraw_selector<U: Data>(mut self, selector: impl Fn(&T) -> &U + 'static) -> Self {
+        self.needs_redraw =
+            Box::new(move |old_data: &T, data: &T| !selector(old_data).same(selector(data)));
+        self
+    }
+
+    /// Builder-style method to set the size of the plot for unbounded constraints
+    ///
+    /// By default the plot takes all the space it is given. Where that space is unbounded,
+    /// e.g. vertically inside a [`Scroll`](druid::widget::Scroll) or along the main axis of a
+    /// [`Flex`](druid::widget::Flex) without a flex factor, the preferred size is used
+    /// instead. Defaults to 400x300.
+    pub fn with_preferred_size(mut self, size: impl Into<Size>) -> Self {
+        self.layout.preferred_size = size.into();
+        self
+    }
+
+    /// Builder-style method to keep a fixed ratio between the width and the height
+    ///
+    /// The ratio is the width divided by the height. The plot is made as large as possible
+    /// while keeping this ratio, within the constraints given by the parent and the minimum
+    /// and maximum size.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// let plot = Plot::new(|_, _: &(), root| {
+    ///     // ...
+    /// })
+    /// .with_aspect_ratio(16. / 9.);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ratio isn't a finite number greater than zero.
+    pub fn with_aspect_ratio(mut self, aspect_ratio: f64) -> Self {
+        assert!(
+            aspect_ratio.is_finite() && aspect_ratio > 0.,
+            "invalid aspect ratio: {aspect_ratio}"
+        );
+        self.layout.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    /// Builder-style method to set the minimum size of the plot
+    ///
+    /// The constraints of the parent widget take precedence if they require the plot to be
+    /// smaller than this.
+    pub fn with_min_size(mut self, size: impl Into<Size>) -> Self {
+        self.layout.min_size = size.into();
+        self
+    }
+
+    /// Builder-style method to set the maximum size of the plot
+    pub fn with_max_size(mut self, size: impl Into<Size>) -> Self {
+        self.layout.max_size = size.into();
+        self
+    }
+
+    /// Builder-style method to keep the rendered plot in an offscreen image
+    ///
+    /// By default the closure runs every time the widget is painted, which includes paints
+    /// that are caused by other widgets or by the window being exposed. With the cache
+    /// enabled, a copy of the plot is taken after it has been drawn, and later paints just
+    /// draw that image. The closure only runs again when the data changes, the widget is
+    /// resized or an interactive plot is zoomed or panned. This is worth it for plots that
+    /// are expensive to draw.
+    ///
+    /// Note that the closure must then only depend on the data and the [`PlotCtx`], as
+    /// changes to anything else won't show up until the next time the data changes.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use std::sync::Arc;
+    /// let plot = Plot::new(|_, _: &Arc<Vec<f64>>, root| {
+    ///     // draw a huge amount of points
+    /// })
+    /// .with_cache(true);
+    /// ```
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Builder-style method to measure the drawing area in device pixels
+    ///
+    /// By default the drawing area is measured in logical pixels, and the render context
+    /// scales everything up to the resolution of the display. Plotters rounds every
+    /// coordinate to a whole pixel though, so on a display with a scale factor of 2, lines
+    /// and markers can only be placed on every other device pixel. With device pixels, the
+    /// drawing area is as large as the plot on the display, e.g. twice the width and height
+    /// of the widget. Stroke widths, font sizes and margins given to plotters are then in
+    /// device pixels as well, multiply them with [`PlotCtx::scale`] to keep them in logical
+    /// pixels.
+    ///
+    /// ```rust
+    /// # use plotters_druid::Plot;
+    /// # use plotters::prelude::*;
+    /// let plot = Plot::interactive(0.0..1.0, 0.0..1.0, |ctx, _: &(), root| {
+    ///     let chart = ChartBuilder::on(&root)
+    ///         .x_label_area_size(30. * ctx.scale())
+    ///         .build_cartesian_2d(ctx.x_range(), ctx.y_range())?;
+    ///     // ...
+    ///     # Ok(())
+    /// })
+    /// .with_device_pixels(true);
+    /// ```
+    pub fn with_device_pixels(mut self, device_pixels: bool) -> Self {
+        self.device_pixels = device_pixels;
+        self
+    }
+
+    /// Translate a position in the widget to the data coordinates of the chart
+    ///
+    /// This uses the mapping that the closure passed to [`PlotCtx::set_coord_mapping`] the
+    /// last time the plot was drawn. Returns `None` if there is no such mapping, or if the
+    /// position is outside of the chart.
+    pub fn to_data(&self, pos: Point) -> Option<(f64, f64)> {
+        let coord_mapping = self.coord_mapping.as_ref()?;
+        coord_mapping.reverse_translate(self.to_pixel(pos))
+    }
+
+    /// The plotters pixel at a position in the widget.
+    fn to_pixel(&self, pos: Point) -> (i32, i32) {
+        ((pos.x * self.scale) as i32, (pos.y * self.scale) as i32)
+    }
+
+    /// The tooltip to show for the given cursor position.
+    fn hover_at(&self, pos: Point) -> Option<Hover> {
+        let coord_mapping = self.coord_mapping.as_ref()?;
+        let (series, point) = tooltip::nearest_point(
+            &self.tooltip_series,
+            &**coord_mapping,
+            self.to_pixel(pos),
+            self.scale,
+        )?;
+        Some(Hover {
+            cursor: pos,
+            series,
+            point,
+        })
+    }
+
+    fn set_hover(&mut self, ctx: &mut EventCtx, hover: Option<Hover>) {
+        if hover != self.hover {
+            self.hover = hover;
+            self.tooltip_changed = true;
+            ctx.request_paint();
+        }
+    }
+
+    /// Errors from saving the plot happen in `event`, so they can be reported right away.
+    fn report_export_error(&self, ctx: &mut EventCtx, data: &mut T, result: PlotResult) {
+        if let (Err(error), Some(on_error)) = (result, &self.on_error) {
+            on_error(ctx, data, &*error);
+        }
+        ctx.set_handled();
+    }
+
+    fn report_error(&mut self, error: Box<dyn Error>, target: Target) {
+        let message = error.to_string();
+        if self.last_error_message.as_ref() == Some(&message) {
+            return;
+        }
+        self.last_error_message = Some(message);
+
+        if self.on_error.is_none() {
+            return;
+        }
+        if let Some(sink) = &self.ext_event_sink {
+            if sink.submit_command(REPORT_ERROR, (), target).is_ok() {
+                self.pending_error = Some(error);
+            }
+        }
     }
 }
 
@@ -114,19 +842,107 @@ impl<T> Widget<T> for Plot<T>
 where
     T: Data,
 {
-    fn event(&mut self, _: &mut druid::EventCtx, _: &druid::Event, _: &mut T, _: &druid::Env) {}
+    fn event(
+        &mut self,
+        ctx: &mut druid::EventCtx,
+        event: &druid::Event,
+        data: &mut T,
+        env: &druid::Env,
+    ) {
+        if let druid::Event::Command(cmd) = event {
+            if cmd.is(REPORT_ERROR) {
+                if let (Some(error), Some(on_error)) = (self.pending_error.take(), &self.on_error) {
+                    on_error(ctx, data, &*error);
+                }
+                ctx.set_handled();
+            }
+            if let Some(export) = cmd.get(SAVE_PLOT_PNG) {
+                let size = export.size.unwrap_or_else(|| ctx.size());
+                let result = self.save_png(data, env, size, export);
+                self.report_export_error(ctx, data, result);
+            }
+            #[cfg(feature = "svg")]
+            if let Some(export) = cmd.get(SAVE_PLOT_SVG) {
+                let size = export.size.unwrap_or_else(|| ctx.size());
+                let result = self.save_svg(data, env, size, export);
+                self.report_export_error(ctx, data, result);
+            }
+        }
+
+        if let (druid::Event::MouseMove(mouse), Some(on_hover)) = (event, &self.on_hover) {
+            on_hover(ctx, data, self.to_data(mouse.pos));
+        }
+
+        if let druid::Event::MouseMove(mouse) = event {
+            let hover = if ctx.is_active() {
+                None
+            } else {
+                self.hover_at(mouse.pos)
+            };
+            self.set_hover(ctx, hover);
+        }
+
+        if let Some(view) = &mut self.view {
+            match event {
+                druid::Event::Wheel(mouse) => {
+                    view.zoom(mouse.pos, mouse.wheel_delta.y);
+                    self.snapshot = None;
+                    self.hover = None;
+                    ctx.request_paint();
+                    ctx.set_handled();
+                }
+                druid::Event::MouseDown(mouse) if mouse.button.is_left() => {
+                    if mouse.count == 2 {
+                        view.reset();
+                        self.snapshot = None;
+                        ctx.request_paint();
+                    } else {
+                        self.drag_pos = Some(mouse.pos);
+                        ctx.set_active(true);
+                    }
+                    ctx.set_handled();
+                }
+                druid::Event::MouseMove(mouse) if ctx.is_active() => {
+                    if let Some(last_pos) = self.drag_pos.replace(mouse.pos) {
+                        view.pan(mouse.pos - last_pos);
+                        self.snapshot = None;
+                        ctx.request_paint();
+                    }
+                    ctx.set_handled();
+                }
+                druid::Event::MouseUp(mouse) if mouse.button.is_left() => {
+                    self.drag_pos = None;
+                    ctx.set_active(false);
+                    ctx.set_handled();
+                }
+                _ => {}
+            }
+        }
+    }
 
     fn lifecycle(
         &mut self,
-        _: &mut druid::LifeCycleCtx,
-        _: &druid::LifeCycle,
+        ctx: &mut druid::LifeCycleCtx,
+        event: &druid::LifeCycle,
         _: &T,
         _: &druid::Env,
     ) {
+        match event {
+            druid::LifeCycle::WidgetAdded => {
+                self.ext_event_sink = Some(ctx.get_external_handle());
+            }
+            druid::LifeCycle::HotChanged(false) if self.hover.is_some() => {
+                self.hover = None;
+                self.tooltip_changed = true;
+                ctx.request_paint();
+            }
+            _ => {}
+        }
     }
 
     fn update(&mut self, ctx: &mut druid::UpdateCtx, old_data: &T, data: &T, _env: &druid::Env) {
-        if !old_data.same(data) {
+        if (self.needs_redraw)(old_data, data) || ctx.env_changed() {
+            self.snapshot = None;
             ctx.request_paint();
         }
     }
@@ -138,17 +954,255 @@ where
         _: &T,
         _: &druid::Env,
     ) -> druid::Size {
-        bc.max()
+        bc.debug_check("Plot");
+        self.layout.size(bc)
     }
 
-    fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &T, _: &druid::Env) {
-        let druid::Size { width, height } = ctx.size();
-        let size = (width as u32, height as u32);
-        let backend = PietBackend {
+    fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &T, env: &druid::Env) {
+        let size = ctx.size();
+        let scale = ctx.window().get_scale().unwrap_or_default().x();
+        let bounds = size.to_rect();
+        let widget_id = ctx.widget_id();
+
+        // Without the cache, the closure runs for every paint that isn't just for the tooltip.
+        let tooltip_changed = std::mem::take(&mut self.tooltip_changed);
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .filter(|_| self.cache || tooltip_changed);
+        let snapshot = snapshot.filter(|snapshot| snapshot.size == size && snapshot.scale == scale);
+        if let Some(snapshot) = snapshot {
+            ctx.draw_image(&snapshot.image, bounds, InterpolationMode::NearestNeighbor);
+        } else {
+            self.snapshot = None;
+            let success = self.paint_plot(ctx.render_ctx, data, env, size, scale, widget_id);
+
+            // Only keep a snapshot if the whole plot was just drawn, and if the cache or
+            // the tooltips need it. Tooltips only show up while the mouse is over the plot.
+            let fully_painted = ctx
+                .region()
+                .rects()
+                .iter()
+                .any(|rect| rect.intersect(bounds) == bounds);
+            let tooltips = ctx.is_hot() && !self.tooltip_series.is_empty();
+            if success && fully_painted && (self.cache || tooltips) {
+                if let Ok(image) = ctx.capture_image_area(bounds) {
+                    self.snapshot = Some(Snapshot { image, size, scale });
+                }
+            }
+        }
+
+        self.paint_tooltip(ctx, env);
+    }
+}
+
+impl<T: Data> Plot<T> {
+    /// Run the plot closure on the backend, and draw the error placeholder if it fails.
+    fn draw(&self, backend: PietBackend, data: &T, env: &Env) -> (PlotCtx, PlotResult) {
+        let size = backend.size;
+        let scale = backend.scale();
+        let root = backend.into_drawing_area();
+
+        let mut plot_ctx = PlotCtx {
             size,
-            render_ctx: ctx.render_ctx,
+            scale,
+            env: env.clone(),
+            theme: PlotTheme::from_env(env, scale),
+            x_range: self.view.as_ref().map_or(0.0..1.0, |view| view.x.clone()),
+            y_range: self.view.as_ref().map_or(0.0..1.0, |view| view.y.clone()),
+            plotting_area: None,
+            coord_mapping: None,
+            tooltip_series: Vec::new(),
         };
+        let result = (self.plot)(&mut plot_ctx, data, &root);
+        if let Err(error) = &result {
+            match &self.error_placeholder {
+                Some(error_placeholder) => error_placeholder(size, &**error, &root),
+                None => draw_error_message(scale, &**error, &root),
+            }
+        }
+        (plot_ctx, result)
+    }
+
+    /// Draw the plot into an image and write it to a PNG file.
+    fn save_png(&self, data: &T, env: &Env, size: Size, export: &PngExport) -> PlotResult {
+        let width = (size.width * export.scale).round() as usize;
+        let height = (size.height * export.scale).round() as usize;
+
+        let mut device = Device::new()?;
+        let mut target = device.bitmap_target(width, height, export.scale)?;
+        let mut render_ctx = target.render_context();
+        let backend = PietBackend::with_scale(
+            logical_size(size),
+            self.plot_scale(export.scale),
+            &mut render_ctx,
+        );
+        let (_, result) = self.draw(backend, data, env);
+        render_ctx.finish()?;
+        drop(render_ctx);
+        result?;
+
+        target.save_to_file(&export.path)?;
+        Ok(())
+    }
+
+    /// Draw the plot on a piet-svg render context and write it to an SVG file.
+    #[cfg(feature = "svg")]
+    fn save_svg(&self, data: &T, env: &Env, size: Size, export: &SvgExport) -> PlotResult {
+        use plotters_piet::piet_svg;
+
+        let mut render_ctx = piet_svg::RenderContext::new(size);
+        let backend = PietBackend::new_svg(logical_size(size), &mut render_ctx);
+        let (_, result) = self.draw(backend, data, env);
+        render_ctx.finish()?;
+        result?;
 
-        (self.plot)(size, data, &backend.into_drawing_area());
+        let file = std::fs::File::create(&export.path)?;
+        render_ctx.write(std::io::BufWriter::new(file))?;
+        Ok(())
+    }
+
+    /// The number of plotters pixels per logical pixel on a display with the given scale.
+    fn plot_scale(&self, display_scale: f64) -> f64 {
+        if self.device_pixels {
+            display_scale
+        } else {
+            1.
+        }
+    }
+
+    /// Run the plot closure and keep what it tells us about the chart, returns whether
+    /// it succeeded.
+    ///
+    /// `display_scale` is the number of device pixels per logical pixel of `render_ctx`.
+    fn paint_plot(
+        &mut self,
+        render_ctx: &mut Piet,
+        data: &T,
+        env: &Env,
+        size: Size,
+        display_scale: f64,
+        widget_id: WidgetId,
+    ) -> bool {
+        let scale = self.plot_scale(display_scale);
+        let backend = PietBackend::with_scale(logical_size(size), scale, render_ctx);
+        let (plot_ctx, result) = self.draw(backend, data, env);
+
+        self.scale = scale;
+        self.coord_mapping = plot_ctx.coord_mapping;
+        self.tooltip_series = plot_ctx.tooltip_series;
+
+        if let Some(view) = &mut self.view {
+            view.plotting_area = match plot_ctx.plotting_area {
+                Some((x, y)) => Rect::new(
+                    x.start as f64 / scale,
+                    y.start as f64 / scale,
+                    x.end as f64 / scale,
+                    y.end as f64 / scale,
+                ),
+                None => size.to_rect(),
+            };
+        }
+
+        match result {
+            Ok(()) => {
+                self.last_error_message = None;
+                true
+            }
+            Err(error) => {
+                self.report_error(error, Target::Widget(widget_id));
+                false
+            }
+        }
+    }
+
+    fn paint_tooltip(&self, ctx: &mut PaintCtx, env: &Env) {
+        let hover = match self.hover {
+            Some(hover) => hover,
+            None => return,
+        };
+        let (series, coord_mapping) =
+            match (self.tooltip_series.get(hover.series), &self.coord_mapping) {
+                (Some(series), Some(coord_mapping)) => (series, coord_mapping),
+                _ => return,
+            };
+        let point = match series.points.get(hover.point) {
+            Some(point) => *point,
+            None => return,
+        };
+
+        // Plotters draws markers around the center of the pixel.
+        let (x, y) = coord_mapping.translate(&point);
+        let pos = Point::new((x as f64 + 0.5) / self.scale, (y as f64 + 0.5) / self.scale);
+        let text = (self.tooltip_format)(&series.name, point);
+        tooltip::paint_tooltip(ctx, env, hover.cursor, pos, text);
+    }
+}
+
+/// The size of the drawing area for a widget of the given size, in logical pixels.
+fn logical_size(size: Size) -> (u32, u32) {
+    (size.width as u32, size.height as u32)
+}
+
+/// The default error placeholder, prints the error message in the middle of the plot.
+fn draw_error_message(scale: f64, error: &dyn Error, root: &DrawingArea<PietBackend, Shift>) {
+    use plotters::prelude::*;
+    use plotters::style::text_anchor::{HPos, Pos, VPos};
+
+    let style = ("sans-serif", 16. * scale)
+        .into_font()
+        .color(&RED)
+        .pos(Pos::new(HPos::Center, VPos::Center));
+    let (width, height) = root.dim_in_pixel();
+    let center = (width as i32 / 2, height as i32 / 2);
+    root.draw_text(&format!("Plot error: {error}"), &style, center)
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Data)]
+    struct AppState {
+        measurements: Arc<Vec<f64>>,
+        search_text: String,
+    }
+
+    fn app_state() -> AppState {
+        AppState {
+            measurements: Arc::new(vec![1., 2.]),
+            search_text: String::new(),
+        }
+    }
+
+    #[test]
+    fn redraw_on_any_change_by_default() {
+        let plot = Plot::new(|_, _: &AppState, _| {});
+        let old = app_state();
+        let typed = AppState {
+            search_text: "temperature".into(),
+            ..old.clone()
+        };
+        assert!(!(plot.needs_redraw)(&old, &old.clone()));
+        assert!((plot.needs_redraw)(&old, &typed));
+    }
+
+    #[test]
+    fn redraw_selector() {
+        let plot =
+            Plot::new(|_, _: &AppState, _| {}).with_redraw_selector(|data| &data.measurements);
+        let old = app_state();
+        let typed = AppState {
+            search_text: "temperature".into(),
+            ..old.clone()
+        };
+        let measured = AppState {
+            measurements: Arc::new(vec![1., 2., 3.]),
+            ..old.clone()
+        };
+        assert!(!(plot.needs_redraw)(&old, &typed));
+        assert!((plot.needs_redraw)(&old, &measured));
     }
 }
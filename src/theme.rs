@@ -0,0 +1,223 @@
+//! Env keys for the default styling of plots.
+//!
+//! Setting these keys with [`env_scope`](druid::WidgetExt::env_scope) styles all the plots
+//! in a subtree. The plot closure gets the resulting styles through [`PlotCtx::theme`],
+//! which falls back to the colors and fonts of the druid theme for keys that are not set.
+//!
+//! ```rust
+//! # use druid::{Color, Widget, WidgetExt};
+//! # use plotters::prelude::*;
+//! use plotters_druid::{theme, Plot};
+//!
+//! fn build_plot_widget() -> impl Widget<()> {
+//!     Plot::with_ctx(|ctx, _, root| {
+//!         let theme = ctx.theme();
+//!         root.fill(&theme.background())?;
+//!         let mut chart = ChartBuilder::on(root)
+//!             .x_label_area_size(30)
+//!             .y_label_area_size(30)
+//!             .build_cartesian_2d(0.0..1.0, 0.0..1.0)?;
+//!         chart
+//!             .configure_mesh()
+//!             .axis_style(theme.axis_style())
+//!             .bold_line_style(theme.grid_style())
+//!             .light_line_style(theme.light_grid_style())
+//!             .label_style(theme.label_style())
+//!             .draw()?;
+//!         let points = [(0.1, 0.2), (0.9, 0.7)];
+//!         chart.draw_series(LineSeries::new(points, theme.series_style(0)))?;
+//!         Ok(())
+//!     })
+//!     .env_scope(|env, _| {
+//!         env.set(theme::BACKGROUND_COLOR, Color::WHITE);
+//!         env.set(theme::AXIS_COLOR, Color::BLACK);
+//!     })
+//! }
+//! ```
+//!
+//! [`PlotCtx::theme`]: crate::PlotCtx::theme
+
+use druid::{Color, Env, FontDescriptor, FontFamily, FontStyle, FontWeight, Key};
+use plotters::style::{self, Color as _, FontDesc, RGBAColor, RGBColor, ShapeStyle, TextStyle};
+
+/// The background of the plot, transparent by default.
+pub const BACKGROUND_COLOR: Key<Color> = Key::new("plotters-druid.theme.background-color");
+/// The color of the axes, [`druid::theme::TEXT_COLOR`] by default.
+pub const AXIS_COLOR: Key<Color> = Key::new("plotters-druid.theme.axis-color");
+/// The color of the grid lines, [`druid::theme::BORDER_DARK`] by default.
+pub const GRID_COLOR: Key<Color> = Key::new("plotters-druid.theme.grid-color");
+/// The color of the labels, [`druid::theme::TEXT_COLOR`] by default.
+pub const LABEL_COLOR: Key<Color> = Key::new("plotters-druid.theme.label-color");
+/// The font of the labels, [`druid::theme::UI_FONT`] by default.
+pub const LABEL_FONT: Key<FontDescriptor> = Key::new("plotters-druid.theme.label-font");
+/// The colors of the data series, picked with [`PlotTheme::series_color`].
+pub const SERIES_COLORS: [Key<Color>; 8] = [
+    Key::new("plotters-druid.theme.series-color-0"),
+    Key::new("plotters-druid.theme.series-color-1"),
+    Key::new("plotters-druid.theme.series-color-2"),
+    Key::new("plotters-druid.theme.series-color-3"),
+    Key::new("plotters-druid.theme.series-color-4"),
+    Key::new("plotters-druid.theme.series-color-5"),
+    Key::new("plotters-druid.theme.series-color-6"),
+    Key::new("plotters-druid.theme.series-color-7"),
+];
+
+const DEFAULT_SERIES_COLORS: [(u8, u8, u8); 8] = [
+    (31, 119, 180),
+    (255, 127, 14),
+    (44, 160, 44),
+    (214, 39, 40),
+    (148, 103, 189),
+    (140, 86, 75),
+    (227, 119, 194),
+    (23, 190, 207),
+];
+
+/// The styles that the plot closure should use, read from the [`Env`].
+///
+/// Line widths and font sizes are already converted to the pixels of the drawing area,
+/// see [`PlotCtx::scale`](crate::PlotCtx::scale).
+#[derive(Debug, Clone)]
+pub struct PlotTheme {
+    background: RGBAColor,
+    axis: RGBAColor,
+    grid: RGBAColor,
+    label: RGBAColor,
+    label_family: String,
+    label_size: f64,
+    label_weight: FontWeight,
+    label_font_style: FontStyle,
+    series: [RGBAColor; 8],
+    scale: f64,
+}
+
+impl PlotTheme {
+    pub(crate) fn from_env(env: &Env, scale: f64) -> PlotTheme {
+        let color = |key: Key<Color>, default: Key<Color>| {
+            to_plotters_color(&env.try_get(key).unwrap_or_else(|_| env.get(default)))
+        };
+        let font = env
+            .try_get(LABEL_FONT)
+            .unwrap_or_else(|_| env.get(druid::theme::UI_FONT));
+
+        PlotTheme {
+            background: to_plotters_color(
+                &env.try_get(BACKGROUND_COLOR).unwrap_or(Color::TRANSPARENT),
+            ),
+            axis: color(AXIS_COLOR, druid::theme::TEXT_COLOR),
+            grid: color(GRID_COLOR, druid::theme::BORDER_DARK),
+            label: color(LABEL_COLOR, druid::theme::TEXT_COLOR),
+            label_family: font_family_name(&font.family).to_owned(),
+            label_size: plotters_piet::font_size_for_em(font.size) * scale,
+            label_weight: font.weight,
+            label_font_style: font.style,
+            series: std::array::from_fn(|i| {
+                let (r, g, b) = DEFAULT_SERIES_COLORS[i];
+                let color = env.try_get(SERIES_COLORS[i].clone());
+                to_plotters_color(&color.unwrap_or_else(|_| Color::rgb8(r, g, b)))
+            }),
+            scale,
+        }
+    }
+
+    /// The color to fill the background with.
+    pub fn background(&self) -> RGBAColor {
+        self.background
+    }
+
+    /// The style for the axes, to be used with `axis_style`.
+    pub fn axis_style(&self) -> ShapeStyle {
+        self.line(self.axis, 1.)
+    }
+
+    /// The style for the major grid lines, to be used with `bold_line_style`.
+    pub fn grid_style(&self) -> ShapeStyle {
+        self.line(self.grid, 1.)
+    }
+
+    /// The style for the minor grid lines, to be used with `light_line_style`.
+    pub fn light_grid_style(&self) -> ShapeStyle {
+        self.line(self.grid.mix(0.5), 1.)
+    }
+
+    /// The style for labels, legends and captions.
+    pub fn label_style(&self) -> TextStyle<'_> {
+        FontDesc::new(
+            self.label_family.as_str().into(),
+            self.label_size,
+            font_style(self.label_weight, self.label_font_style),
+        )
+        .color(&self.label)
+    }
+
+    /// The color of the series with the given index, this wraps around after 8 series.
+    pub fn series_color(&self, idx: usize) -> RGBAColor {
+        self.series[idx % self.series.len()]
+    }
+
+    /// A line style for the series with the given index.
+    pub fn series_style(&self, idx: usize) -> ShapeStyle {
+        self.line(self.series_color(idx), 2.)
+    }
+
+    /// A line of the given width in logical pixels.
+    fn line(&self, color: RGBAColor, width: f64) -> ShapeStyle {
+        ShapeStyle::from(color).stroke_width(((width * self.scale).round() as u32).max(1))
+    }
+}
+
+pub(crate) fn to_plotters_color(color: &Color) -> RGBAColor {
+    let (r, g, b, a) = color.as_rgba8();
+    RGBColor(r, g, b).mix(a as f64 / 255.)
+}
+
+/// The name that plotters uses for a font family.
+fn font_family_name(family: &FontFamily) -> &str {
+    // Plotters doesn't know about the system UI font.
+    if *family == FontFamily::SYSTEM_UI {
+        "sans-serif"
+    } else {
+        family.name()
+    }
+}
+
+fn font_style(weight: FontWeight, slant: FontStyle) -> style::FontStyle {
+    match slant {
+        FontStyle::Italic => style::FontStyle::Italic,
+        FontStyle::Regular if weight.to_raw() >= FontWeight::BOLD.to_raw() => {
+            style::FontStyle::Bold
+        }
+        FontStyle::Regular => style::FontStyle::Normal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn font_conversion() {
+        assert_eq!(font_family_name(&FontFamily::SYSTEM_UI), "sans-serif");
+        assert_eq!(font_family_name(&FontFamily::MONOSPACE), "monospace");
+        assert_eq!(
+            font_family_name(&FontFamily::new_unchecked("Inter")),
+            "Inter"
+        );
+
+        assert!(matches!(
+            font_style(FontWeight::BOLD, FontStyle::Regular),
+            style::FontStyle::Bold
+        ));
+        assert!(matches!(
+            font_style(FontWeight::REGULAR, FontStyle::Italic),
+            style::FontStyle::Italic
+        ));
+    }
+
+    #[test]
+    fn color_conversion() {
+        let color = to_plotters_color(&Color::rgba8(10, 20, 30, 51));
+        assert_eq!(color.rgb(), (10, 20, 30));
+        assert!((color.alpha() - 0.2).abs() < 1e-9);
+    }
+}
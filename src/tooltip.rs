@@ -0,0 +1,157 @@
+//! Tooltips showing the data point closest to the cursor.
+
+use druid::{
+    piet::{FontFamily, Text, TextLayout, TextLayoutBuilder},
+    theme, Env, PaintCtx, Point, Rect, RenderContext, Size, Vec2,
+};
+use plotters::coord::ReverseCoordTranslate;
+
+/// How far away from the cursor a point may be to get a tooltip, in logical pixels.
+const MAX_DISTANCE: f64 = 20.;
+
+/// Offset of the tooltip box from the cursor, in logical pixels.
+const CURSOR_OFFSET: f64 = 12.;
+
+const PADDING: f64 = 4.;
+
+/// A series of points that the plot closure registered for tooltips.
+pub(crate) struct HoverSeries {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// The point that the tooltip is currently shown for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Hover {
+    /// The cursor position, in logical pixels.
+    pub cursor: Point,
+    pub series: usize,
+    pub point: usize,
+}
+
+/// Find the point that is closest to the cursor on screen.
+///
+/// `cursor` and the result are measured in plotters pixels, `scale` is used to convert
+/// the maximum distance.
+pub(crate) fn nearest_point(
+    series: &[HoverSeries],
+    coord_mapping: &dyn ReverseCoordTranslate<From = (f64, f64)>,
+    cursor: (i32, i32),
+    scale: f64,
+) -> Option<(usize, usize)> {
+    // Points that are not inside the chart can't be hovered.
+    coord_mapping.reverse_translate(cursor)?;
+
+    let max_distance = MAX_DISTANCE * scale;
+    let mut nearest = None;
+    let mut nearest_distance = max_distance * max_distance;
+    for (series_idx, s) in series.iter().enumerate() {
+        for (point_idx, point) in s.points.iter().enumerate() {
+            let (x, y) = coord_mapping.translate(point);
+            let dx = (x - cursor.0) as f64;
+            let dy = (y - cursor.1) as f64;
+            let distance = dx * dx + dy * dy;
+            if distance <= nearest_distance {
+                nearest = Some((series_idx, point_idx));
+                nearest_distance = distance;
+            }
+        }
+    }
+    nearest
+}
+
+/// The default tooltip text, the series name followed by the coordinates.
+pub(crate) fn default_format(name: &str, (x, y): (f64, f64)) -> String {
+    if name.is_empty() {
+        format!("x: {x:.3}\ny: {y:.3}")
+    } else {
+        format!("{name}\nx: {x:.3}\ny: {y:.3}")
+    }
+}
+
+/// Draw the tooltip box next to the cursor and mark the hovered point.
+///
+/// `point` is the position of the hovered data point in logical pixels.
+pub(crate) fn paint_tooltip(
+    ctx: &mut PaintCtx,
+    env: &Env,
+    cursor: Point,
+    point: Point,
+    text: String,
+) {
+    let text_color = env.get(theme::TEXT_COLOR);
+    let layout = ctx
+        .text()
+        .new_text_layout(text)
+        .font(FontFamily::SYSTEM_UI, env.get(theme::TEXT_SIZE_NORMAL))
+        .text_color(text_color)
+        .build();
+    let layout = match layout {
+        Ok(layout) => layout,
+        Err(_) => return,
+    };
+
+    let marker = druid::kurbo::Circle::new(point, 4.);
+    ctx.stroke(marker, &text_color, 1.5);
+
+    let box_size = layout.size() + Size::new(2. * PADDING, 2. * PADDING);
+    let bounds = ctx.size().to_rect();
+    let mut origin = cursor + Vec2::new(CURSOR_OFFSET, CURSOR_OFFSET);
+    // Flip the box to the other side of the cursor if it doesn't fit.
+    if origin.x + box_size.width > bounds.x1 {
+        origin.x = cursor.x - CURSOR_OFFSET - box_size.width;
+    }
+    if origin.y + box_size.height > bounds.y1 {
+        origin.y = cursor.y - CURSOR_OFFSET - box_size.height;
+    }
+
+    let rect = Rect::from_origin_size(origin, box_size).to_rounded_rect(3.);
+    ctx.fill(rect, &env.get(theme::BACKGROUND_LIGHT));
+    ctx.stroke(rect, &env.get(theme::BORDER_DARK), 1.);
+    ctx.draw_text(&layout, origin + Vec2::new(PADDING, PADDING));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::coord::{cartesian::Cartesian2d, types::RangedCoordf64};
+
+    // Plotters maps the y axis to a reversed pixel range to make it point up.
+    #[allow(clippy::reversed_empty_ranges)]
+    fn coord_spec() -> Cartesian2d<RangedCoordf64, RangedCoordf64> {
+        // 100x100 pixels showing 0..10 in both directions, the y axis points up.
+        Cartesian2d::<RangedCoordf64, RangedCoordf64>::new(0.0..10.0, 0.0..10.0, (0..100, 100..0))
+    }
+
+    fn series() -> Vec<HoverSeries> {
+        vec![
+            HoverSeries {
+                name: "a".to_owned(),
+                points: vec![(1., 1.), (5., 5.)],
+            },
+            HoverSeries {
+                name: "b".to_owned(),
+                points: vec![(6., 5.)],
+            },
+        ]
+    }
+
+    fn nearest(cursor: (i32, i32), scale: f64) -> Option<(usize, usize)> {
+        nearest_point(&series(), &coord_spec(), cursor, scale)
+    }
+
+    #[test]
+    fn nearest_point_in_pixels() {
+        assert_eq!(nearest((49, 50), 1.), Some((0, 1)));
+        assert_eq!(nearest((58, 52), 1.), Some((1, 0)));
+        assert_eq!(nearest((10, 88), 1.), Some((0, 0)));
+    }
+
+    #[test]
+    fn nearest_point_too_far_away() {
+        assert_eq!(nearest((90, 10), 1.), None);
+        // The same distance is close enough at a higher scale.
+        assert_eq!(nearest((85, 50), 1.), None);
+        assert_eq!(nearest((85, 50), 2.), Some((1, 0)));
+    }
+}
@@ -0,0 +1,120 @@
+//! Zooming and panning of interactive cartesian plots.
+
+use std::ops::Range;
+
+use druid::{Point, Rect, Vec2};
+
+/// How much the view is zoomed per unit of wheel delta. One notch of a typical
+/// mouse wheel is reported as a delta of 100 (or more, depending on the platform).
+const ZOOM_SPEED: f64 = 0.002;
+
+/// The ranges of an interactive cartesian plot that are currently visible.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CartesianView {
+    initial: (Range<f64>, Range<f64>),
+    pub x: Range<f64>,
+    pub y: Range<f64>,
+    /// Where in the widget the ranges are drawn, in logical pixels.
+    pub plotting_area: Rect,
+}
+
+impl CartesianView {
+    pub fn new(x: Range<f64>, y: Range<f64>) -> CartesianView {
+        CartesianView {
+            initial: (x.clone(), y.clone()),
+            x,
+            y,
+            plotting_area: Rect::ZERO,
+        }
+    }
+
+    /// Go back to the ranges that the plot was created with.
+    pub fn reset(&mut self) {
+        self.x = self.initial.0.clone();
+        self.y = self.initial.1.clone();
+    }
+
+    /// The data coordinates at the given widget position.
+    pub fn to_data(&self, pos: Point) -> (f64, f64) {
+        let area = self.plotting_area;
+        let fx = (pos.x - area.x0) / area.width();
+        let fy = (pos.y - area.y0) / area.height();
+
+        // The y axis points up in the chart, but down in the widget.
+        (
+            self.x.start + fx * (self.x.end - self.x.start),
+            self.y.end - fy * (self.y.end - self.y.start),
+        )
+    }
+
+    /// Zoom in or out for the given wheel delta, keeping the data point under the cursor
+    /// in place.
+    pub fn zoom(&mut self, around: Point, wheel_delta: f64) {
+        if self.plotting_area.area() <= 0. {
+            return;
+        }
+
+        let factor = (wheel_delta * ZOOM_SPEED).exp();
+        let (cx, cy) = self.to_data(around);
+        self.x = cx + (self.x.start - cx) * factor..cx + (self.x.end - cx) * factor;
+        self.y = cy + (self.y.start - cy) * factor..cy + (self.y.end - cy) * factor;
+    }
+
+    /// Move the view such that the data follows the cursor, which moved by `delta`
+    /// logical pixels.
+    pub fn pan(&mut self, delta: Vec2) {
+        if self.plotting_area.area() <= 0. {
+            return;
+        }
+
+        let dx = delta.x / self.plotting_area.width() * (self.x.end - self.x.start);
+        let dy = delta.y / self.plotting_area.height() * (self.y.end - self.y.start);
+        self.x = self.x.start - dx..self.x.end - dx;
+        self.y = self.y.start + dy..self.y.end + dy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view() -> CartesianView {
+        let mut view = CartesianView::new(0.0..10.0, -1.0..1.0);
+        view.plotting_area = Rect::new(10., 10., 110., 60.);
+        view
+    }
+
+    #[test]
+    fn to_data() {
+        let view = view();
+        assert_eq!(view.to_data(Point::new(10., 10.)), (0., 1.));
+        assert_eq!(view.to_data(Point::new(110., 60.)), (10., -1.));
+        assert_eq!(view.to_data(Point::new(60., 35.)), (5., 0.));
+    }
+
+    #[test]
+    fn zoom_keeps_cursor_in_place() {
+        let mut view = view();
+        let cursor = Point::new(35., 20.);
+        let before = view.to_data(cursor);
+
+        view.zoom(cursor, -100.);
+        let after = view.to_data(cursor);
+
+        assert!(view.x.end - view.x.start < 10.);
+        assert!((before.0 - after.0).abs() < 1e-9);
+        assert!((before.1 - after.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pan_and_reset() {
+        let mut view = view();
+
+        view.pan(Vec2::new(50., -25.));
+        assert_eq!(view.x, -5.0..5.0);
+        assert_eq!(view.y, -2.0..0.0);
+
+        view.reset();
+        assert_eq!(view, self::view());
+    }
+}